@@ -40,6 +40,27 @@ pub fn encode_null(buf: Option<BytesMut>) -> BytesMut {
   v
 }
 
+pub fn pipelined_integers(count: usize) -> BytesMut {
+  let mut buf = BytesMut::with_capacity(count * 4);
+
+  for _ in 0..count {
+    buf.extend_from_slice(b":1\r\n");
+  }
+  buf
+}
+
+// copies the remaining bytes before decoding each frame, for comparison with `decode_all`
+fn decode_all_copying(buf: &[u8]) -> Vec<Frame> {
+  let mut frames = Vec::new();
+  let mut remaining = buf.to_vec();
+
+  while let Ok((Some(frame), len)) = decode(&remaining) {
+    frames.push(frame);
+    remaining = remaining[len..].to_vec();
+  }
+  frames
+}
+
 pub fn rand_array(len: usize, null_every: usize, str_len: usize) -> BytesMut {
   let arr_len_digits = redis_protocol::digits_in_number(len);
   let str_len_digits = redis_protocol::digits_in_number(str_len);
@@ -217,5 +238,43 @@ mod tests {
     });
   }
 
+  // pipelined decoding
+
+  #[bench]
+  fn bench_decode_all_1k_pipelined_integers(b: &mut Bencher) {
+    let buf = pipelined_integers(1000);
+
+    b.iter(|| {
+      black_box(decode_all(&buf));
+    });
+  }
+
+  #[bench]
+  fn bench_decode_all_10k_pipelined_integers(b: &mut Bencher) {
+    let buf = pipelined_integers(10000);
+
+    b.iter(|| {
+      black_box(decode_all(&buf));
+    });
+  }
+
+  #[bench]
+  fn bench_decode_all_copying_1k_pipelined_integers(b: &mut Bencher) {
+    let buf = pipelined_integers(1000);
+
+    b.iter(|| {
+      black_box(decode_all_copying(&buf));
+    });
+  }
+
+  #[bench]
+  fn bench_decode_all_copying_10k_pipelined_integers(b: &mut Bencher) {
+    let buf = pipelined_integers(10000);
+
+    b.iter(|| {
+      black_box(decode_all_copying(&buf));
+    });
+  }
+
 
 }
\ No newline at end of file
@@ -6,27 +6,75 @@ use utils::CRLF;
 
 use nom::{
   be_u8,
-  Err as NomError
+  be_u32,
+  Context,
+  Err as NomError,
+  ErrorKind as NomErrorKind,
+  IResult,
+  Needed
 };
 
-use bytes::BytesMut;
+use bytes::{
+  Bytes,
+  BytesMut
+};
 
+use std::cmp;
 use std::str;
-use std::num::ParseIntError;
+use std::collections::BTreeSet;
+use std::io::{
+  Cursor,
+  ErrorKind as IoErrorKind,
+  Read
+};
+use std::num::{
+  ParseIntError,
+  ParseFloatError
+};
 
 const NULL_LEN: isize = -1;
 
-fn to_isize(s: &str) -> Result<isize, ParseIntError> {
-  s.parse::<isize>()
+// `str::parse` tolerates a leading `+`, so length prefixes are checked to be either `-1` or only digits. this
+// avoids a proxy and a server disagreeing on where a frame ends. returns the custom error code on failure, so lengths
+// with too many digits can be reported separately from malformed lengths.
+fn to_prefix_len(s: &[u8]) -> Result<isize, u32> {
+  if s == b"-1" {
+    return Ok(NULL_LEN);
+  }
+  if s.is_empty() || !s.iter().all(|b| b.is_ascii_digit()) {
+    return Err(utils::INVALID_LENGTH_ERROR);
+  }
+
+  str::from_utf8(s).ok()
+    .and_then(|s| s.parse::<isize>().ok())
+    .ok_or(utils::LENGTH_OUT_OF_RANGE_ERROR)
 }
 
 fn to_i64(s: &str) -> Result<i64, ParseIntError> {
   s.parse::<i64>()
 }
 
-fn map_error(s: &str) -> Frame {
-  utils::read_cluster_error(s)
-    .unwrap_or_else(|| Frame::Error(s.to_owned()))
+fn to_f64(s: &str) -> Result<f64, ParseFloatError> {
+  s.parse::<f64>()
+}
+
+fn to_bignumber<'a>(s: &'a str) -> Result<&'a str, RedisProtocolError<'a>> {
+  let digits = s.strip_prefix('-').unwrap_or(s);
+
+  if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+    Ok(s)
+  }else{
+    Err(RedisProtocolError::new(RedisProtocolErrorKind::DecodeError, "Invalid big number."))
+  }
+}
+
+fn map_error(s: &str, split_redirections: bool) -> Frame {
+  if split_redirections {
+    utils::read_cluster_error(s)
+      .unwrap_or_else(|| Frame::Error(s.to_owned()))
+  }else{
+    Frame::Error(s.to_owned())
+  }
 }
 
 fn isize_to_usize<'a>(s: isize) -> Result<usize, RedisProtocolError<'a>> {
@@ -43,7 +91,69 @@ named!(read_to_crlf<&[u8]>, terminated!(take_until!(CRLF), take!(2)));
 
 named!(read_to_crlf_s<&str>, map_res!(read_to_crlf, str::from_utf8));
 
-named!(read_prefix_len<isize>, map_res!(read_to_crlf_s, to_isize));
+fn line_too_long(input: &[u8]) -> IResult<&[u8], &[u8]> {
+  Err(NomError::Failure(Context::Code(input, NomErrorKind::Custom(utils::LINE_TOO_LONG_ERROR))))
+}
+
+// mirrors `read_to_crlf`, but gives up once `max` bytes have been scanned without finding the terminator
+fn read_to_crlf_bounded(input: &[u8], max: usize) -> IResult<&[u8], &[u8]> {
+  let scan_len = cmp::min(input.len(), max.saturating_add(CRLF.len()));
+
+  match read_to_crlf(&input[0..scan_len]) {
+    Ok((_, line)) => Ok((&input[line.len() + CRLF.len()..], line)),
+    Err(NomError::Incomplete(_)) if scan_len == max.saturating_add(CRLF.len()) => line_too_long(input),
+    Err(e) => Err(e)
+  }
+}
+
+// reads a line terminated by either CRLF or a bare LF, only consuming the bytes actually used by the terminator
+fn read_to_lf(input: &[u8], max: Option<usize>) -> IResult<&[u8], &[u8]> {
+  let scan_len = match max {
+    Some(max) => cmp::min(input.len(), max.saturating_add(CRLF.len())),
+    None      => input.len()
+  };
+
+  match input[0..scan_len].iter().position(|b| *b == b'\n') {
+    Some(pos) => {
+      let line = if pos > 0 && input[pos - 1] == b'\r' {
+        &input[0..pos - 1]
+      }else{
+        &input[0..pos]
+      };
+
+      if max.map(|max| line.len() > max).unwrap_or(false) {
+        line_too_long(input)
+      }else{
+        Ok((&input[pos + 1..], line))
+      }
+    },
+    None if max.map(|max| scan_len == max.saturating_add(CRLF.len())).unwrap_or(false) => line_too_long(input),
+    None => Err(NomError::Incomplete(Needed::Unknown))
+  }
+}
+
+named_args!(read_line(options: DecodeOptions) <&[u8]>,
+  switch!(value!((options.lenient_newlines, options.max_line_len)),
+    (true, max)        => call!(read_to_lf, max) |
+    (false, Some(max)) => call!(read_to_crlf_bounded, max) |
+    (false, None)      => call!(read_to_crlf)
+  )
+);
+
+named_args!(read_line_s(options: DecodeOptions) <&str>, map_res!(call!(read_line, options), str::from_utf8));
+
+fn read_prefix_len(input: &[u8]) -> IResult<&[u8], isize> {
+  let (remaining, line) = match read_to_crlf(input) {
+    Ok(result)                        => result,
+    Err(NomError::Incomplete(needed)) => return Err(NomError::Incomplete(needed)),
+    Err(_)                            => return Err(NomError::Failure(Context::Code(input, NomErrorKind::Custom(utils::INVALID_LENGTH_ERROR))))
+  };
+
+  match to_prefix_len(line) {
+    Ok(len)   => Ok((remaining, len)),
+    Err(code) => Err(NomError::Failure(Context::Code(input, NomErrorKind::Custom(code))))
+  }
+}
 
 named!(frame_type<FrameKind>,
   switch!(be_u8,
@@ -51,24 +161,63 @@ named!(frame_type<FrameKind>,
     ERROR_BYTE        => value!(FrameKind::Error) |
     INTEGER_BYTE      => value!(FrameKind::Integer) |
     BULKSTRING_BYTE   => value!(FrameKind::BulkString) |
-    ARRAY_BYTE        => value!(FrameKind::Array)
+    ARRAY_BYTE        => value!(FrameKind::Array) |
+    RESP3_NULL_BYTE   => value!(FrameKind::NullResp3) |
+    DOUBLE_BYTE       => value!(FrameKind::Double) |
+    BOOLEAN_BYTE      => value!(FrameKind::Boolean) |
+    BIGNUMBER_BYTE    => value!(FrameKind::BigNumber) |
+    VERBATIM_BYTE     => value!(FrameKind::VerbatimString) |
+    MAP_BYTE          => value!(FrameKind::Map) |
+    SET_BYTE          => value!(FrameKind::Set) |
+    PUSH_BYTE         => value!(FrameKind::Push) |
+    ATTRIBUTE_BYTE    => value!(FrameKind::Attribute)
   )
 );
 
-named!(parse_simplestring<Frame>,
+named_args!(parse_simplestring(options: DecodeOptions) <Frame>,
   do_parse!(
-    data: read_to_crlf_s >>
+    data: call!(read_line_s, options) >>
     (Frame::SimpleString(data.to_owned()))
   )
 );
 
-named!(parse_integer<Frame>,
+// the line is read before the integer is parsed so that errors reading the line are not reported as invalid integers.
+// a stray \r or \n inside the line is left in the line and rejected as an invalid integer.
+named_args!(parse_integer(options: DecodeOptions) <Frame>,
   do_parse!(
-    data: map_res!(read_to_crlf_s, to_i64) >>
+    line: call!(read_line_s, options) >>
+    data: return_error!(NomErrorKind::Custom(utils::INVALID_INTEGER_ERROR), expr_res!(to_i64(line))) >>
     (Frame::Integer(data))
   )
 );
 
+named_args!(parse_double(options: DecodeOptions) <Frame>,
+  do_parse!(
+    data: map_res!(call!(read_line_s, options), to_f64) >>
+    (Frame::Double(data))
+  )
+);
+
+named_args!(parse_bignumber(options: DecodeOptions) <Frame>,
+  do_parse!(
+    line: call!(read_line_s, options) >>
+    data: return_error!(NomErrorKind::Custom(utils::INVALID_INTEGER_ERROR), expr_res!(to_bignumber(line))) >>
+    (Frame::BigNumber(data.to_owned()))
+  )
+);
+
+named!(read_boolean<bool>,
+  terminated!(
+    alt!(
+      value!(true, char!('t')) |
+      value!(false, char!('f'))
+    ),
+    tag!(CRLF)
+  )
+);
+
+named!(parse_boolean<Frame>, map!(read_boolean, Frame::Boolean));
+
 // assumes the '$-1\r\n' has been consumed already, since nulls look like bulk strings until the length prefix is parsed,
 // and parsing the length prefix consumes the trailing \r\n in the underlying `terminated!` call
 named!(parse_null<Frame>,
@@ -77,7 +226,16 @@ named!(parse_null<Frame>,
   )
 );
 
-named!(parse_error<Frame>, map!(read_to_crlf_s, map_error));
+named!(parse_null_resp3<Frame>,
+  do_parse!(
+    tag!(CRLF) >>
+    (Frame::NullResp3)
+  )
+);
+
+named_args!(parse_error(options: DecodeOptions) <Frame>,
+  map!(call!(read_line_s, options), |s| map_error(s, options.split_redirections))
+);
 
 named_args!(parse_bulkstring(len: isize) <Frame>,
   do_parse!(
@@ -93,187 +251,1986 @@ named!(parse_bulkstring_or_null<Frame>,
   )
 );
 
-named_args!(parse_array_frames(len: usize) <Vec<Frame>>, count!(parse_frame, len));
+fn split_verbatim<'a>(d: &'a [u8]) -> Result<(VerbatimFormat, &'a [u8]), RedisProtocolError<'a>> {
+  if d.len() >= utils::VERBATIM_FORMAT_LEN && d[3] == b':' {
+    Ok((VerbatimFormat::from_bytes([d[0], d[1], d[2]]), &d[utils::VERBATIM_FORMAT_LEN..]))
+  }else{
+    Err(RedisProtocolError::new(RedisProtocolErrorKind::DecodeError, "Invalid verbatim string format."))
+  }
+}
 
-named!(parse_array<Frame>,
-  switch!(read_prefix_len,
-    NULL_LEN => call!(parse_null) |
-    len      => do_parse!(
-      size: map_res!(value!(len), isize_to_usize) >>
-      frames: call!(parse_array_frames, size) >>
-      (Frame::Array(frames))
-    )
+// verbatim strings are bulk strings where the first 4 bytes of the payload are the format, such as `txt:`
+named!(read_verbatimstring<(VerbatimFormat, &[u8])>,
+  do_parse!(
+    len: map_res!(read_prefix_len, isize_to_usize) >>
+    d: terminated!(take!(len), tag!(CRLF)) >>
+    parts: return_error!(NomErrorKind::Custom(utils::INVALID_VERBATIM_ERROR), expr_res!(split_verbatim(d))) >>
+    (parts)
   )
 );
 
-named!(parse_frame<Frame>,
-  switch!(frame_type,
-    FrameKind::SimpleString => call!(parse_simplestring) |
-    FrameKind::Error        => call!(parse_error) |
-    FrameKind::Integer      => call!(parse_integer) |
-    FrameKind::BulkString   => call!(parse_bulkstring_or_null) |
-    FrameKind::Array        => call!(parse_array)
+named!(parse_verbatimstring<Frame>,
+  map!(read_verbatimstring, |(format, data)| Frame::VerbatimString { format, data: data.to_vec() })
+);
+
+named!(parse_streamed_chunk<&[u8]>,
+  do_parse!(
+    char!(CHUNK_BYTE as char) >>
+    len: map_res!(read_prefix_len, isize_to_usize) >>
+    d: terminated!(take!(len), tag!(CRLF)) >>
+    (d)
   )
 );
 
-/// Attempt to parse the contents of `buf`, returning the first valid frame and the number of bytes consumed.
-/// If the byte slice contains an incomplete frame then `None` is returned.
-pub fn decode(buf: &[u8]) -> Result<(Option<Frame>, usize), RedisProtocolError> {
-  let len = buf.len();
+// assumes the '$' has been consumed already. chunks are concatenated into a single bulk string.
+named!(parse_streamed_string<Frame>,
+  do_parse!(
+    tag!("?\r\n") >>
+    chunks: many_till!(parse_streamed_chunk, tag!(utils::STREAM_END)) >>
+    (Frame::BulkString(chunks.0.concat()))
+  )
+);
 
-  match parse_frame(buf) {
-    Ok((remaining, frame))       => Ok((Some(frame), len - remaining.len())),
-    Err(NomError::Incomplete(_)) => Ok((None, 0)),
-    Err(e)                       => Err(e.into())
-  }
-}
+// a streamed aggregate ends with a line containing only `.`, and any other line starting with `.` is an error
+named!(parse_stream_terminator<()>,
+  do_parse!(
+    char!(STREAM_END_BYTE as char) >>
+    return_error!(NomErrorKind::Custom(utils::INVALID_STREAM_END_ERROR), tag!(CRLF)) >>
+    ()
+  )
+);
 
-/// Attempt to parse the contents of `buf`, returning the first valid frame and the number of bytes consumed.
-/// If the byte slice contains an incomplete frame then `None` is returned.
-///
-/// **The caller is responsible for consuming the underlying bytes.**
-pub fn decode_bytes(buf: &BytesMut) -> Result<(Option<Frame>, usize), RedisProtocolError> {
-  decode(buf)
+// `depth` is the nesting depth of the aggregate being parsed, where a top level aggregate has a depth of 1
+fn check_depth(input: &[u8], options: DecodeOptions, depth: usize) -> IResult<&[u8], ()> {
+  match options.max_depth {
+    Some(max) if depth > max => Err(NomError::Failure(Context::Code(input, NomErrorKind::Custom(utils::MAX_DEPTH_ERROR)))),
+    _                        => Ok((input, ()))
+  }
 }
 
+named_args!(parse_array_frames(len: usize, options: DecodeOptions, depth: usize) <Vec<Frame>>,
+  count!(call!(parse_frame, options, depth), len)
+);
 
-#[cfg(test)]
-mod tests {
-  use super::*;
-  use ::utils;
-  use ::types::*;
+// inner frames are read until a line starting with `.`, which must be the terminator. errors are failures so that the
+// caller does not fall back to parsing the stream header as a length prefix.
+fn parse_streamed_frames(input: &[u8], options: DecodeOptions, depth: usize) -> IResult<&[u8], Vec<Frame>> {
+  let mut frames = Vec::new();
+  let mut input = input;
 
-  use std::fmt;
-  use std::str;
+  loop {
+    let result = match input.first() {
+      None                   => return Err(NomError::Incomplete(Needed::Size(1))),
+      Some(&STREAM_END_BYTE) => return parse_stream_terminator(input).map(|(remaining, _)| (remaining, frames)),
+      Some(_)                => parse_frame(input, options, depth)
+    };
 
-  use nom::Err as NomError;
-  use nom::simple_errors::Context;
+    match result {
+      Ok((remaining, frame))  => {
+        frames.push(frame);
+        input = remaining;
+      },
+      Err(NomError::Error(e)) => return Err(NomError::Failure(e)),
+      Err(e)                  => return Err(e)
+    };
+  }
+}
 
-  const PADDING: &'static str = "FOOBARBAZ";
+// assumes the '*' has been consumed already
+named_args!(parse_streamed_array(options: DecodeOptions, depth: usize) <Frame>,
+  do_parse!(
+    tag!("?\r\n") >>
+    frames: call!(parse_streamed_frames, options, depth) >>
+    (Frame::Array(frames))
+  )
+);
 
-  fn str_to_bytes(s: &str) -> Vec<u8> {
-    s.as_bytes().to_vec()
-  }
+named_args!(parse_array(options: DecodeOptions, depth: usize) <Frame>,
+  preceded!(
+    call!(check_depth, options, depth),
+    alt!(
+      call!(parse_streamed_array, options, depth) |
+      switch!(read_prefix_len,
+        NULL_LEN => call!(parse_null) |
+        len      => do_parse!(
+          size: map_res!(value!(len), isize_to_usize) >>
+          frames: call!(parse_array_frames, size, options, depth) >>
+          (Frame::Array(frames))
+        )
+      )
+    )
+  )
+);
 
-  fn to_bytes(s: &str) -> BytesMut {
-    BytesMut::from(str_to_bytes(s))
-  }
+// used by sets and pushes, which share the array format but cannot be null
+named_args!(parse_aggregate_frames(options: DecodeOptions, depth: usize) <Vec<Frame>>,
+  do_parse!(
+    call!(check_depth, options, depth) >>
+    size: map_res!(read_prefix_len, isize_to_usize) >>
+    frames: call!(parse_array_frames, size, options, depth) >>
+    (frames)
+  )
+);
 
-  fn empty_bytes() -> BytesMut {
-    BytesMut::new()
-  }
+fn check_set_duplicates<'a>(input: &'a [u8], options: DecodeOptions, frames: &[Frame]) -> IResult<&'a [u8], ()> {
+  if options.strict_sets {
+    let mut seen = BTreeSet::new();
 
-  fn pretty_print_panic(e: RedisProtocolError) {
-    match e.context() {
-      Some(c) => match str::from_utf8(c) {
-        Ok(s) => panic!("Error {:?} with {}", e, s),
-        Err(e) => panic!("{:?}", e)
-      },
-      _ => panic!("{:?}", e)
+    if !frames.iter().all(|frame| seen.insert(frame)) {
+      return Err(NomError::Failure(Context::Code(input, NomErrorKind::Custom(utils::DUPLICATE_SET_ELEMENT_ERROR))));
     }
   }
 
-  fn decode_and_verify_some(bytes: &mut BytesMut, expected: &(Option<Frame>, usize)) {
-    let (frame, len) = match decode_bytes(&bytes) {
-      Ok((f, l)) => (f, l),
-      Err(e) => return pretty_print_panic(e)
-    };
+  Ok((input, ()))
+}
 
-    assert_eq!(frame, expected.0, "decoded frame matched");
-    assert_eq!(len, expected.1, "decoded frame len matched");
-  }
+named_args!(parse_set(options: DecodeOptions, depth: usize) <Frame>,
+  do_parse!(
+    frames: call!(parse_aggregate_frames, options, depth) >>
+    call!(check_set_duplicates, options, &frames) >>
+    (Frame::Set(frames))
+  )
+);
 
-  fn decode_and_verify_padded_some(bytes: &mut BytesMut, expected: &(Option<Frame>, usize)) {
-    bytes.extend_from_slice(PADDING.as_bytes());
+// used by maps and attributes, where the length prefix is the number of key/value pairs
+named_args!(parse_map_pairs(options: DecodeOptions, depth: usize) <Vec<(Frame, Frame)>>,
+  do_parse!(
+    call!(check_depth, options, depth) >>
+    size: map_res!(read_prefix_len, isize_to_usize) >>
+    pairs: count!(pair!(call!(parse_frame, options, depth), call!(parse_frame, options, depth)), size) >>
+    (pairs)
+  )
+);
 
-    let (frame, len) = match decode_bytes(&bytes) {
-      Ok((f, l)) => (f, l),
-      Err(e) => return pretty_print_panic(e)
-    };
+named_args!(parse_frame(options: DecodeOptions, depth: usize) <Frame>,
+  switch!(frame_type,
+    FrameKind::SimpleString => call!(parse_simplestring, options) |
+    FrameKind::Error        => call!(parse_error, options) |
+    FrameKind::Integer      => call!(parse_integer, options) |
+    FrameKind::BulkString   => alt!(parse_streamed_string | parse_bulkstring_or_null) |
+    FrameKind::Array        => call!(parse_array, options, depth + 1) |
+    FrameKind::NullResp3    => call!(parse_null_resp3) |
+    FrameKind::Double       => call!(parse_double, options) |
+    FrameKind::Boolean      => call!(parse_boolean) |
+    FrameKind::BigNumber    => call!(parse_bignumber, options) |
+    FrameKind::VerbatimString => call!(parse_verbatimstring) |
+    FrameKind::Map          => map!(call!(parse_map_pairs, options, depth + 1), Frame::Map) |
+    FrameKind::Set          => call!(parse_set, options, depth + 1) |
+    FrameKind::Push         => map!(call!(parse_aggregate_frames, options, depth + 1), Frame::Push) |
+    FrameKind::Attribute    => map!(call!(parse_map_pairs, options, depth + 1), Frame::Attribute)
+  )
+);
 
-    assert_eq!(frame, expected.0, "decoded frame matched");
-    assert_eq!(len, expected.1, "decoded frame len matched");
+fn not_scalar(input: &[u8]) -> IResult<&[u8], ScalarFrame> {
+  Err(NomError::Failure(Context::Code(input, NomErrorKind::Custom(utils::NOT_SCALAR_ERROR))))
+}
+
+fn to_status_len(s: &[u8]) -> Result<ScalarFrame, RedisProtocolError> {
+  if s.len() <= u16::MAX as usize {
+    Ok(ScalarFrame::StatusLen(s.len() as u16))
+  }else{
+    Err(RedisProtocolError::new(RedisProtocolErrorKind::DecodeError, "Simple string too long."))
   }
+}
 
-  fn decode_and_verify_none(bytes: &mut BytesMut) {
-    let (frame, len) = match decode_bytes(&bytes) {
-      Ok((f, l)) => (f, l),
-      Err(e) => return pretty_print_panic(e)
+// only null bulk strings and arrays are scalars, since anything else would need to be copied
+named!(scalar_frame<ScalarFrame>,
+  switch!(frame_type,
+    FrameKind::SimpleString => map_res!(read_to_crlf, to_status_len) |
+    FrameKind::Error        => value!(ScalarFrame::Error, read_to_crlf) |
+    FrameKind::Integer      => do_parse!(
+      line: read_to_crlf_s >>
+      data: return_error!(NomErrorKind::Custom(utils::INVALID_INTEGER_ERROR), expr_res!(to_i64(line))) >>
+      (ScalarFrame::Int(data))
+    ) |
+    FrameKind::NullResp3    => value!(ScalarFrame::Null, tag!(CRLF)) |
+    FrameKind::BulkString   => switch!(read_prefix_len,
+      NULL_LEN => value!(ScalarFrame::Null) |
+      _        => call!(not_scalar)
+    ) |
+    FrameKind::Array        => switch!(read_prefix_len,
+      NULL_LEN => value!(ScalarFrame::Null) |
+      _        => call!(not_scalar)
+    ) |
+    _                       => call!(not_scalar)
+  )
+);
+
+// the view parsers mirror the frame parsers above, but return subslices of the input instead of copying
+
+named_args!(view_simplestring(options: DecodeOptions) <FrameView>, map!(call!(read_line, options), FrameView::SimpleString));
+
+named_args!(view_error(options: DecodeOptions) <FrameView>, map!(call!(read_line, options), FrameView::Error));
+
+named_args!(view_integer(options: DecodeOptions) <FrameView>,
+  do_parse!(
+    line: call!(read_line_s, options) >>
+    data: return_error!(NomErrorKind::Custom(utils::INVALID_INTEGER_ERROR), expr_res!(to_i64(line))) >>
+    (FrameView::Integer(data))
+  )
+);
+
+named_args!(view_double(options: DecodeOptions) <FrameView>, map!(map_res!(call!(read_line_s, options), to_f64), FrameView::Double));
+
+named_args!(view_bignumber(options: DecodeOptions) <FrameView>,
+  do_parse!(
+    line: call!(read_line_s, options) >>
+    data: return_error!(NomErrorKind::Custom(utils::INVALID_INTEGER_ERROR), expr_res!(to_bignumber(line))) >>
+    (FrameView::BigNumber(data.as_bytes()))
+  )
+);
+
+named_args!(view_bulkstring(len: isize) <FrameView>,
+  do_parse!(
+    d: terminated!(take!(len), take!(2)) >>
+    (FrameView::BulkString(d))
+  )
+);
+
+named!(view_bulkstring_or_null<FrameView>,
+  switch!(read_prefix_len,
+    NULL_LEN => value!(FrameView::Null) |
+    len      => call!(view_bulkstring, len)
+  )
+);
+
+// mirrors `parse_streamed_frames`
+fn view_streamed_frames<'a>(input: &'a [u8], options: DecodeOptions, depth: usize) -> IResult<&'a [u8], Vec<FrameView<'a>>> {
+  let mut frames = Vec::new();
+  let mut input = input;
+
+  loop {
+    let result = match input.first() {
+      None                   => return Err(NomError::Incomplete(Needed::Size(1))),
+      Some(&STREAM_END_BYTE) => return parse_stream_terminator(input).map(|(remaining, _)| (remaining, frames)),
+      Some(_)                => view_frame(input, options, depth)
     };
 
-    assert!(frame.is_none());
-    assert_eq!(len, 0);
+    match result {
+      Ok((remaining, frame))  => {
+        frames.push(frame);
+        input = remaining;
+      },
+      Err(NomError::Error(e)) => return Err(NomError::Failure(e)),
+      Err(e)                  => return Err(e)
+    };
   }
+}
 
-  #[test]
-  fn should_decode_llen_res_example() {
-    let expected = (Some(Frame::Integer(48293)), 8);
-    let mut bytes: BytesMut = ":48293\r\n".into();
+named_args!(view_array(options: DecodeOptions, depth: usize) <FrameView>,
+  preceded!(
+    call!(check_depth, options, depth),
+    alt!(
+      map!(preceded!(tag!("?\r\n"), call!(view_streamed_frames, options, depth)), FrameView::Array) |
+      switch!(read_prefix_len,
+        NULL_LEN => value!(FrameView::Null) |
+        len      => do_parse!(
+          size: map_res!(value!(len), isize_to_usize) >>
+          frames: count!(call!(view_frame, options, depth), size) >>
+          (FrameView::Array(frames))
+        )
+      )
+    )
+  )
+);
 
-    decode_and_verify_some(&mut bytes, &expected);
-    decode_and_verify_padded_some(&mut bytes, &expected);
-  }
+named_args!(view_aggregate_frames(options: DecodeOptions, depth: usize) <Vec<FrameView>>,
+  do_parse!(
+    call!(check_depth, options, depth) >>
+    size: map_res!(read_prefix_len, isize_to_usize) >>
+    frames: count!(call!(view_frame, options, depth), size) >>
+    (frames)
+  )
+);
 
-  #[test]
-  fn should_decode_simple_string() {
-    let expected = (Some(Frame::SimpleString("string".into())), 9);
-    let mut bytes: BytesMut = "+string\r\n".into();
+named_args!(view_map_pairs(options: DecodeOptions, depth: usize) <Vec<(FrameView, FrameView)>>,
+  do_parse!(
+    call!(check_depth, options, depth) >>
+    size: map_res!(read_prefix_len, isize_to_usize) >>
+    pairs: count!(pair!(call!(view_frame, options, depth), call!(view_frame, options, depth)), size) >>
+    (pairs)
+  )
+);
 
-    decode_and_verify_some(&mut bytes, &expected);
-    decode_and_verify_padded_some(&mut bytes, &expected);
-  }
+named_args!(view_frame(options: DecodeOptions, depth: usize) <FrameView>,
+  switch!(frame_type,
+    FrameKind::SimpleString => call!(view_simplestring, options) |
+    FrameKind::Error        => call!(view_error, options) |
+    FrameKind::Integer      => call!(view_integer, options) |
+    FrameKind::BulkString   => call!(view_bulkstring_or_null) |
+    FrameKind::Array        => call!(view_array, options, depth + 1) |
+    FrameKind::NullResp3    => value!(FrameView::NullResp3, tag!(CRLF)) |
+    FrameKind::Double       => call!(view_double, options) |
+    FrameKind::Boolean      => map!(read_boolean, FrameView::Boolean) |
+    FrameKind::BigNumber    => call!(view_bignumber, options) |
+    FrameKind::VerbatimString => map!(read_verbatimstring, |(format, data)| FrameView::VerbatimString { format, data }) |
+    FrameKind::Map          => map!(call!(view_map_pairs, options, depth + 1), FrameView::Map) |
+    FrameKind::Set          => map!(call!(view_aggregate_frames, options, depth + 1), FrameView::Set) |
+    FrameKind::Push         => map!(call!(view_aggregate_frames, options, depth + 1), FrameView::Push) |
+    FrameKind::Attribute    => map!(call!(view_map_pairs, options, depth + 1), FrameView::Attribute)
+  )
+);
 
-  #[test]
-  fn should_decode_bulk_string() {
-    let expected = (Some(Frame::BulkString(str_to_bytes("foo"))), 9);
-    let mut bytes: BytesMut = "$3\r\nfoo\r\n".into();
+named!(bulkstring_header<isize>, preceded!(char!(BULKSTRING_BYTE as char), read_prefix_len));
 
-    decode_and_verify_some(&mut bytes, &expected);
-    decode_and_verify_padded_some(&mut bytes, &expected);
+// the number of bytes needed to complete a bulk string, if `buf` starts with a complete bulk string header
+fn bulkstring_remaining(buf: &[u8]) -> Option<usize> {
+  match bulkstring_header(buf) {
+    Ok((remaining, len)) if len >= 0 => Some((len as usize + CRLF.len()).saturating_sub(remaining.len())),
+    _ => None
   }
+}
 
-  #[test]
-  fn should_decode_array_no_nulls() {
-    let expected = (Some(Frame::Array(vec![
-      Frame::SimpleString("Foo".into()),
-      Frame::SimpleString("Bar".into())
-    ])), 16);
-    let mut bytes: BytesMut = "*2\r\n+Foo\r\n+Bar\r\n".into();
+// the iterative parser reads one token at a time, where aggregates only read their header and their inner frames are
+// collected on an explicit stack instead of by recursion
 
-    decode_and_verify_some(&mut bytes, &expected);
-    decode_and_verify_padded_some(&mut bytes, &expected);
-  }
+enum Token {
+  Frame(Frame),
+  Aggregate(FrameKind, usize),
+  StreamedArray
+}
 
-  #[test]
-  fn should_decode_array_nulls() {
-    let mut bytes: BytesMut = "*3\r\n$3\r\nFoo\r\n$-1\r\n$3\r\nBar\r\n".into();
+struct PendingAggregate {
+  kind: FrameKind,
+  // `None` for streamed aggregates, which are complete once the terminator is read
+  expected: Option<usize>,
+  frames: Vec<Frame>
+}
 
-    let expected = (Some(Frame::Array(vec![
-      Frame::BulkString(str_to_bytes("Foo")),
-      Frame::Null,
-      Frame::BulkString(str_to_bytes("Bar"))
-    ])), bytes.len());
+impl PendingAggregate {
 
-    decode_and_verify_some(&mut bytes, &expected);
-    decode_and_verify_padded_some(&mut bytes, &expected);
+  fn new(kind: FrameKind, len: usize) -> PendingAggregate {
+    let expected = match kind {
+      FrameKind::Map | FrameKind::Attribute => len * 2,
+      _ => len
+    };
+
+    PendingAggregate { kind, expected: Some(expected), frames: Vec::new() }
   }
 
-  #[test]
-  fn should_decode_normal_error() {
-    let mut bytes: BytesMut = "-WRONGTYPE Operation against a key holding the wrong kind of value\r\n".into();
-    let expected = (Some(Frame::Error("WRONGTYPE Operation against a key holding the wrong kind of value".into())), bytes.len());
+  fn streamed(kind: FrameKind) -> PendingAggregate {
+    PendingAggregate { kind, expected: None, frames: Vec::new() }
+  }
 
-    decode_and_verify_some(&mut bytes, &expected);
-    decode_and_verify_padded_some(&mut bytes, &expected);
+  fn is_streamed(&self) -> bool {
+    self.expected.is_none()
   }
 
-  #[test]
-  fn should_decode_moved_error() {
-    let mut bytes: BytesMut = "-MOVED 3999 127.0.0.1:6381\r\n".into();
+  fn is_complete(&self) -> bool {
+    match self.expected {
+      Some(expected) => self.frames.len() >= expected,
+      None           => false
+    }
+  }
+
+  fn into_frame(self) -> Frame {
+    match self.kind {
+      FrameKind::Map | FrameKind::Attribute => {
+        let mut pairs = Vec::with_capacity(self.frames.len() / 2);
+        let mut frames = self.frames.into_iter();
+
+        while let (Some(key), Some(value)) = (frames.next(), frames.next()) {
+          pairs.push((key, value));
+        }
+
+        if self.kind == FrameKind::Map {
+          Frame::Map(pairs)
+        }else{
+          Frame::Attribute(pairs)
+        }
+      },
+      FrameKind::Set  => Frame::Set(self.frames),
+      FrameKind::Push => Frame::Push(self.frames),
+      _               => Frame::Array(self.frames)
+    }
+  }
+
+}
+
+named_args!(aggregate_token(kind: FrameKind) <Token>,
+  map!(map_res!(read_prefix_len, isize_to_usize), |len| Token::Aggregate(kind.clone(), len))
+);
+
+named_args!(parse_token(options: DecodeOptions) <Token>,
+  switch!(frame_type,
+    FrameKind::SimpleString => map!(call!(parse_simplestring, options), Token::Frame) |
+    FrameKind::Error        => map!(call!(parse_error, options), Token::Frame) |
+    FrameKind::Integer      => map!(call!(parse_integer, options), Token::Frame) |
+    FrameKind::BulkString   => map!(alt!(parse_streamed_string | parse_bulkstring_or_null), Token::Frame) |
+    FrameKind::NullResp3    => map!(parse_null_resp3, Token::Frame) |
+    FrameKind::Double       => map!(call!(parse_double, options), Token::Frame) |
+    FrameKind::Boolean      => map!(parse_boolean, Token::Frame) |
+    FrameKind::BigNumber    => map!(call!(parse_bignumber, options), Token::Frame) |
+    FrameKind::VerbatimString => map!(parse_verbatimstring, Token::Frame) |
+    FrameKind::Array        => alt!(
+      value!(Token::StreamedArray, tag!("?\r\n")) |
+      switch!(read_prefix_len,
+        NULL_LEN => value!(Token::Frame(Frame::Null)) |
+        len      => map!(map_res!(value!(len), isize_to_usize), |len| Token::Aggregate(FrameKind::Array, len))
+      )
+    ) |
+    FrameKind::Map          => call!(aggregate_token, FrameKind::Map) |
+    FrameKind::Set          => call!(aggregate_token, FrameKind::Set) |
+    FrameKind::Push         => call!(aggregate_token, FrameKind::Push) |
+    FrameKind::Attribute    => call!(aggregate_token, FrameKind::Attribute)
+  )
+);
+
+/// Whether or not `byte` is the type byte of a RESP2 or RESP3 frame, and therefore a valid first byte of a frame.
+pub fn is_valid_frame_start(byte: u8) -> bool {
+  FrameKind::from_byte(byte).is_some()
+}
+
+/// Attempt to parse the contents of `buf`, returning the first valid frame and the number of bytes consumed.
+/// If the byte slice contains an incomplete frame then `None` is returned.
+///
+/// Incomplete frames, including aggregates with an incomplete inner frame, consume no bytes, so the same buffer can be
+/// parsed again from the start once more bytes arrive.
+pub fn decode(buf: &[u8]) -> Result<(Option<Frame>, usize), RedisProtocolError> {
+  decode_with_options(buf, &DecodeOptions::default())
+}
+
+/// Attempt to parse the contents of `buf` according to `options`, returning the first valid frame and the number of bytes consumed.
+/// If the byte slice contains an incomplete frame then `None` is returned.
+pub fn decode_with_options<'a>(buf: &'a [u8], options: &DecodeOptions) -> Result<(Option<Frame>, usize), RedisProtocolError<'a>> {
+  // parsing stops once `max_total_size` bytes have been read, so a frame that needs more than that is incomplete even though more bytes are available
+  let input = match options.max_total_size {
+    Some(max) => &buf[0..cmp::min(buf.len(), max)],
+    None      => buf
+  };
+  let len = input.len();
+
+  match parse_frame(input, *options, 0) {
+    Ok((remaining, frame))       => Ok((Some(frame), len - remaining.len())),
+    Err(NomError::Incomplete(_)) => {
+      if input.len() < buf.len() {
+        Err(RedisProtocolError::new(RedisProtocolErrorKind::DecodeError, "Max total size exceeded."))
+      }else{
+        Ok((None, 0))
+      }
+    },
+    Err(e)                       => Err(e.into())
+  }
+}
+
+/// Attempt to parse the contents of `buf` with strict validation, returning the first valid frame and the number of bytes consumed.
+/// If the byte slice contains an incomplete frame then `None` is returned.
+///
+/// This currently rejects sets that contain duplicate elements. See `DecodeOptions::strict_sets` for more information.
+pub fn decode_strict(buf: &[u8]) -> Result<(Option<Frame>, usize), RedisProtocolError> {
+  let options = DecodeOptions {
+    strict_sets: true,
+    ..Default::default()
+  };
+
+  decode_with_options(buf, &options)
+}
+
+/// Attempt to parse the contents of `buf`, returning the first valid frame and the number of bytes consumed.
+/// If the byte slice contains an incomplete frame then `None` is returned.
+///
+/// **The caller is responsible for consuming the underlying bytes.**
+pub fn decode_bytes(buf: &BytesMut) -> Result<(Option<Frame>, usize), RedisProtocolError> {
+  decode(buf)
+}
+
+/// Attempt to parse the contents of `buf` according to `options`, returning the first valid frame and the number of bytes consumed.
+/// If the byte slice contains an incomplete frame then `None` is returned.
+///
+/// **The caller is responsible for consuming the underlying bytes.**
+pub fn decode_bytes_with_options<'a>(buf: &'a BytesMut, options: &DecodeOptions) -> Result<(Option<Frame>, usize), RedisProtocolError<'a>> {
+  decode_with_options(buf, options)
+}
+
+/// Attempt to parse a frame from the current position of `cursor`, advancing the cursor past the frame.
+/// If the remaining bytes contain an incomplete frame then `None` is returned and the cursor is not moved.
+pub fn decode_cursor<'a>(cursor: &mut Cursor<&'a [u8]>) -> Result<Option<Frame>, RedisProtocolError<'a>> {
+  let buf: &'a [u8] = cursor.get_ref();
+  let offset = cmp::min(cursor.position() as usize, buf.len());
+
+  match decode(&buf[offset..])? {
+    (Some(frame), len) => {
+      cursor.set_position((offset + len) as u64);
+      Ok(Some(frame))
+    },
+    (None, _) => Ok(None)
+  }
+}
+
+/// Attempt to parse all complete frames in `buf`, returning the frames and the total number of bytes consumed.
+/// Any trailing incomplete frame is not consumed.
+///
+/// Each frame is parsed in place from the current offset without copying the remaining bytes, so decoding is linear in the
+/// length of `buf`.
+pub fn decode_all(buf: &[u8]) -> Result<(Vec<Frame>, usize), RedisProtocolError> {
+  let mut frames = Vec::new();
+  let mut offset = 0;
+
+  while let (Some(frame), len) = decode(&buf[offset..])? {
+    frames.push(frame);
+    offset += len;
+  }
+
+  Ok((frames, offset))
+}
+
+/// Attempt to parse the contents of `buf`, returning the first valid frame and the number of bytes consumed.
+/// If the byte slice contains an incomplete frame then `None` is returned.
+///
+/// Unlike `decode` this does not use recursion to parse nested aggregate frames, so deeply nested frames cannot overflow the stack.
+/// The output is otherwise identical to `decode`.
+pub fn decode_iterative(buf: &[u8]) -> Result<(Option<Frame>, usize), RedisProtocolError> {
+  let options = DecodeOptions::default();
+  let mut stack: Vec<PendingAggregate> = Vec::new();
+  let mut input = buf;
+
+  loop {
+    let in_stream = stack.last().map(|pending| pending.is_streamed()).unwrap_or(false);
+    let token = if in_stream && input.first() == Some(&STREAM_END_BYTE) {
+      parse_stream_terminator(input).map(|(remaining, _)| (remaining, None))
+    }else{
+      parse_token(input, options).map(|(remaining, token)| (remaining, Some(token)))
+    };
+
+    let (remaining, token) = match token {
+      Ok(result)                   => result,
+      Err(NomError::Incomplete(_)) => return Ok((None, 0)),
+      Err(e)                       => return Err(e.into())
+    };
+    input = remaining;
+
+    let mut frame = match token {
+      Some(Token::Frame(frame)) => frame,
+      Some(Token::Aggregate(kind, len)) => {
+        let pending = PendingAggregate::new(kind, len);
+
+        if pending.is_complete() {
+          pending.into_frame()
+        }else{
+          stack.push(pending);
+          continue;
+        }
+      },
+      Some(Token::StreamedArray) => {
+        stack.push(PendingAggregate::streamed(FrameKind::Array));
+        continue;
+      },
+      // the terminator of the streamed aggregate on the top of the stack
+      None => match stack.pop() {
+        Some(pending) => pending.into_frame(),
+        None => unreachable!()
+      }
+    };
+
+    // move the frame into its parent, folding each parent that is now complete into its own parent
+    loop {
+      match stack.last_mut() {
+        Some(pending) => {
+          pending.frames.push(frame);
+          if !pending.is_complete() {
+            break;
+          }
+        },
+        None => return Ok((Some(frame), buf.len() - input.len()))
+      };
+
+      frame = match stack.pop() {
+        Some(pending) => pending.into_frame(),
+        None => unreachable!()
+      };
+    }
+  }
+}
+
+/// Attempt to parse an inline command from `buf`, returning the command as an array of bulk strings and the number of bytes consumed.
+/// If the byte slice does not contain a complete line then `None` is returned.
+///
+/// Inline commands are space separated arguments on a single line, such as `PING hello\r\n`, and are sent by clients
+/// that do not implement the protocol, such as telnet. Repeated, leading, and trailing whitespace is ignored, so an empty
+/// line produces an empty array. Servers typically use this when a request does not start with `*`.
+pub fn decode_inline(buf: &[u8]) -> Result<(Option<Frame>, usize), RedisProtocolError> {
+  let len = buf.len();
+
+  match read_to_lf(buf, None) {
+    Ok((remaining, line)) => {
+      let args = line.split(|b| *b == b' ' || *b == b'\t')
+        .filter(|arg| !arg.is_empty())
+        .map(|arg| Frame::BulkString(arg.to_vec()))
+        .collect();
+
+      Ok((Some(Frame::Array(args)), len - remaining.len()))
+    },
+    Err(NomError::Incomplete(_)) => Ok((None, 0)),
+    Err(e)                       => Err(e.into())
+  }
+}
+
+/// Attempt to parse the contents of `buf`, returning the first valid frame and the number of bytes consumed, or a hint
+/// describing how many more bytes are needed to complete the frame.
+///
+/// The number of bytes needed is only known when `buf` starts with the complete length prefix of a bulk string.
+pub fn decode_hint(buf: &[u8]) -> Result<DecodeHint, RedisProtocolError> {
+  match decode(buf)? {
+    (Some(frame), len) => Ok(DecodeHint::Complete(frame, len)),
+    (None, _)          => Ok(DecodeHint::NeedMore(bulkstring_remaining(buf)))
+  }
+}
+
+/// Read the total size of the next frame in `buf`, including the bytes that have already been received.
+///
+/// The size is known when `buf` contains a complete frame or starts with the complete length prefix of a bulk string. Otherwise,
+/// such as for incomplete aggregate frames or lines, `FrameSize::Unknown` is returned.
+pub fn next_frame_size(buf: &[u8]) -> Result<FrameSize, RedisProtocolError> {
+  if let Ok((remaining, len)) = bulkstring_header(buf) {
+    if len >= 0 {
+      return Ok(FrameSize::Known(buf.len() - remaining.len() + len as usize + CRLF.len()));
+    }
+  }
+
+  match decode(buf)? {
+    (Some(_), len) => Ok(FrameSize::Known(len)),
+    (None, _)      => Ok(FrameSize::Unknown)
+  }
+}
+
+/// Attempt to parse a frame from the front of `buf`, removing the bytes of the frame from `buf`. If the byte slice contains an
+/// incomplete frame then `None` is returned.
+///
+/// If `buf` starts with a bulk string longer than `threshold` then its header and any bytes that have already been received
+/// are removed from `buf` and returned with a continuation, without waiting for the rest of the bulk string. The remaining
+/// bytes are then read from `buf` with `BulkStringContinuation::next_chunk` as they arrive.
+pub fn decode_streaming(buf: &mut BytesMut, threshold: usize) -> Result<Option<StreamingFrame>, RedisProtocolError<'static>> {
+  let header = match bulkstring_header(buf) {
+    Ok((remaining, len)) if len >= 0 && len as usize > threshold => Some((buf.len() - remaining.len(), len as usize)),
+    _ => None
+  };
+
+  if let Some((header_len, total_len)) = header {
+    let _ = buf.split_to(header_len);
+    let available = buf.split_to(cmp::min(total_len, buf.len())).freeze();
+    let continuation = BulkStringContinuation::new(total_len, available.len());
+
+    return Ok(Some(StreamingFrame::BulkStringStreaming { total_len, available, continuation }));
+  }
+
+  match decode(buf).map_err(|e| e.into_owned())? {
+    (Some(frame), len) => {
+      let _ = buf.split_to(len);
+      Ok(Some(StreamingFrame::Complete(frame)))
+    },
+    (None, _) => Ok(None)
+  }
+}
+
+/// Read from `reader` into `buf` until a complete frame can be parsed, returning the frame and removing its bytes from `buf`.
+///
+/// Any bytes read after the frame are left in `buf` for the next call. Returns a `DecodeError` if the reader reaches EOF
+/// before a complete frame is read.
+pub fn decode_from_reader<R: Read>(reader: &mut R, buf: &mut BytesMut) -> Result<Frame, RedisProtocolError<'static>> {
+  let mut chunk = [0; utils::KB];
+
+  loop {
+    if let (Some(frame), len) = decode(buf).map_err(|e| e.into_owned())? {
+      let _ = buf.split_to(len);
+      return Ok(frame);
+    }
+
+    let read = match reader.read(&mut chunk) {
+      Ok(read) => read,
+      Err(ref e) if e.kind() == IoErrorKind::Interrupted => continue,
+      Err(e) => return Err(RedisProtocolError::new(RedisProtocolErrorKind::Unknown, e.to_string()))
+    };
+
+    if read == 0 {
+      return Err(RedisProtocolError::new(RedisProtocolErrorKind::DecodeError, "Unexpected EOF."));
+    }
+    buf.extend_from_slice(&chunk[0..read]);
+  }
+}
+
+/// Attempt to parse the contents of `buf`, returning the first valid frame and the number of bytes consumed, and
+/// recording the result in `stats`. If the byte slice contains an incomplete frame then `None` is returned.
+pub fn decode_with_stats<'a>(buf: &'a [u8], stats: &mut DecodeStats) -> Result<(Option<Frame>, usize), RedisProtocolError<'a>> {
+  let (frame, len) = decode(buf)?;
+
+  match frame {
+    Some(ref frame) => {
+      *stats.frames.entry(frame.kind()).or_insert(0) += 1;
+      stats.bytes_consumed += len;
+    },
+    None => stats.incomplete += 1
+  };
+
+  Ok((frame, len))
+}
+
+/// Attempt to parse a frame preceded by a 4 byte big endian length header from `buf`, returning the frame and the number of bytes consumed,
+/// including the header. If the byte slice contains an incomplete header or frame then `None` is returned.
+///
+/// See `encode_length_prefixed` for the corresponding encoding function.
+pub fn decode_length_prefixed(buf: &[u8]) -> Result<(Option<Frame>, usize), RedisProtocolError> {
+  let (remaining, len) = match be_u32(buf) {
+    Ok((remaining, len))         => (remaining, len as usize),
+    Err(NomError::Incomplete(_)) => return Ok((None, 0)),
+    Err(e)                       => return Err(e.into())
+  };
+
+  if remaining.len() < len {
+    return Ok((None, 0));
+  }
+
+  match decode(&remaining[0..len])? {
+    (Some(frame), consumed) if consumed == len => Ok((Some(frame), utils::LENGTH_PREFIX_LEN + len)),
+    _ => Err(RedisProtocolError::new(RedisProtocolErrorKind::DecodeError, "Length prefix does not match frame length."))
+  }
+}
+
+/// Attempt to parse the contents of `buf` without copying, returning a view of the first valid frame and the number of bytes consumed.
+/// If the byte slice contains an incomplete frame then `None` is returned.
+///
+/// String payloads in the returned view borrow from `buf` and are not checked for UTF-8 validity. When `buf` is a `Bytes`
+/// the payloads can be sliced out of it without copying with `FrameView::payload_bytes`.
+pub fn decode_view(buf: &[u8]) -> Result<(Option<FrameView>, usize), RedisProtocolError> {
+  decode_view_with_options(buf, &DecodeOptions::default())
+}
+
+/// Attempt to parse the contents of `buf` according to `options` without copying, returning a view of the first valid frame
+/// and the number of bytes consumed. If the byte slice contains an incomplete frame then `None` is returned.
+///
+/// The view parser applies the same `max_depth`, `max_line_len`, `max_total_size`, and `lenient_newlines` limits as
+/// `decode_with_options`. Errors are not split into redirections and sets are not checked for duplicates.
+pub fn decode_view_with_options<'a>(buf: &'a [u8], options: &DecodeOptions) -> Result<(Option<FrameView<'a>>, usize), RedisProtocolError<'a>> {
+  let input = match options.max_total_size {
+    Some(max) => &buf[0..cmp::min(buf.len(), max)],
+    None      => buf
+  };
+  let len = input.len();
+
+  match view_frame(input, *options, 0) {
+    Ok((remaining, frame))       => Ok((Some(frame), len - remaining.len())),
+    Err(NomError::Incomplete(_)) => {
+      if input.len() < buf.len() {
+        Err(RedisProtocolError::new(RedisProtocolErrorKind::DecodeError, "Max total size exceeded."))
+      }else{
+        Ok((None, 0))
+      }
+    },
+    Err(e)                       => Err(e.into())
+  }
+}
+
+/// Attempt to parse a simple reply from `buf` without allocating, returning the reply and the number of bytes consumed.
+/// If the byte slice contains an incomplete reply then `None` is returned.
+///
+/// Integers, simple strings, errors, and nulls are supported. Any other frame, including aggregate frames and non-null bulk strings,
+/// returns a `DecodeError`.
+pub fn decode_scalar(buf: &[u8]) -> Result<(Option<ScalarFrame>, usize), RedisProtocolError> {
+  let len = buf.len();
+
+  match scalar_frame(buf) {
+    Ok((remaining, frame))       => Ok((Some(frame), len - remaining.len())),
+    Err(NomError::Incomplete(_)) => Ok((None, 0)),
+    Err(e)                       => Err(e.into())
+  }
+}
+
+/// Attempt to parse the first frame in `buf`, along with the protocol version it implies.
+///
+/// The version is `Resp3` if the frame or any of its inner frames is a RESP3 type. See `Frame::resp_version` for more information.
+pub fn decode_detect_version(buf: &[u8]) -> Result<(Option<(Frame, RespVersion)>, usize), RedisProtocolError> {
+  match decode(buf)? {
+    (Some(frame), len) => {
+      let version = frame.resp_version();
+      Ok((Some((frame, version)), len))
+    },
+    (None, len) => Ok((None, len))
+  }
+}
+
+/// Attempt to parse the first frame in `buf`, returning a `DecodeError` if the frame does not have the expected `shape`.
+///
+/// Error frames are always returned as-is, regardless of `shape`.
+pub fn decode_expecting(buf: &[u8], shape: ReplyShape) -> Result<(Option<Frame>, usize), RedisProtocolError> {
+  match decode(buf)? {
+    (Some(ref frame), _) if !shape.matches(frame) => {
+      Err(RedisProtocolError::new(RedisProtocolErrorKind::DecodeError, format!("Expected {:?} reply, found {:?}.", shape, frame.kind())))
+    },
+    result => Ok(result)
+  }
+}
+
+/// Attempt to split the first frame off the front of `buf`, returning both the decoded frame and its original bytes.
+///
+/// This can be used to forward frames byte-for-byte, since re-encoding a decoded frame does not always produce the same bytes.
+/// If `buf` contains an incomplete frame then `None` is returned and `buf` is not modified.
+///
+/// The original bytes share the allocation of `buf`. See `decode_raw_with_mode` to copy them instead.
+pub fn decode_raw(buf: &mut BytesMut) -> Result<Option<RawFrame>, RedisProtocolError<'static>> {
+  decode_raw_with_mode(buf, CopyMode::Borrowed)
+}
+
+/// Attempt to split the first frame off the front of `buf`, returning both the decoded frame and its original bytes.
+///
+/// With `CopyMode::Owned` the original bytes and payload are copied into a new allocation, which avoids keeping the allocation of
+/// `buf` alive while the frame is held. With `CopyMode::Borrowed` they share the allocation of `buf`, although `Bytes` stores
+/// short payloads inline. The decoded frame always owns its data, so use the payload for zero-copy access to string contents.
+pub fn decode_raw_with_mode(buf: &mut BytesMut, mode: CopyMode) -> Result<Option<RawFrame>, RedisProtocolError<'static>> {
+  let (frame, len) = match decode(buf).map_err(|e| e.into_owned())? {
+    (Some(frame), len) => (frame, len),
+    (None, _) => return Ok(None)
+  };
+
+  let raw = buf.split_to(len);
+  let bytes = match mode {
+    CopyMode::Owned    => Bytes::from(&raw[..]),
+    CopyMode::Borrowed => raw.freeze()
+  };
+  let payload = raw_payload(&frame, &bytes);
+
+  Ok(Some(RawFrame { frame, bytes, payload }))
+}
+
+// string payloads always end just before the trailing CRLF of the frame, so they can be sliced out of the raw bytes without parsing them again
+fn raw_payload(frame: &Frame, bytes: &Bytes) -> Option<Bytes> {
+  let end = bytes.len() - CRLF.len();
+
+  let start = match *frame {
+    Frame::SimpleString(_)
+      | Frame::Error(_)
+      | Frame::Moved(_)
+      | Frame::Ask(_)
+      | Frame::BigNumber(_) => 1,
+    Frame::BulkString(ref b) if !bytes.starts_with(utils::STREAMED_STRING_START.as_bytes()) => end - b.len(),
+    Frame::VerbatimString { ref data, .. } => end - data.len(),
+    _ => return None
+  };
+
+  Some(bytes.slice(start, end))
+}
+
+/// A decoder for connections that are only used for publish-subscribe messages.
+///
+/// Only push frames, subscription confirmations, and RESP2 publish-subscribe messages are returned. Any other frame, such as
+/// the reply to a regular command, is an error.
+#[derive(Clone, Debug, Default)]
+pub struct PushDecoder {
+  buf: BytesMut
+}
+
+impl PushDecoder {
+
+  pub fn new() -> PushDecoder {
+    PushDecoder {
+      buf: BytesMut::new()
+    }
+  }
+
+  /// Append bytes read from the connection to the buffer.
+  pub fn extend_from_slice(&mut self, data: &[u8]) {
+    self.buf.extend_from_slice(data);
+  }
+
+  /// Read a reference to the bytes buffered but not yet decoded.
+  pub fn buffer(&self) -> &BytesMut {
+    &self.buf
+  }
+
+  /// Attempt to decode the next frame from the buffer, returning `None` if the buffer does not contain a complete frame.
+  ///
+  /// Frames that are not expected on a publish-subscribe connection return a `DecodeError`. The unexpected frame is
+  /// removed from the buffer, so decoding can continue with the frames that follow it.
+  pub fn decode(&mut self) -> Result<Option<Frame>, RedisProtocolError<'static>> {
+    let (frame, len) = match decode(&self.buf).map_err(|e| e.into_owned())? {
+      (Some(frame), len) => (frame, len),
+      (None, _) => return Ok(None)
+    };
+    let _ = self.buf.split_to(len);
+
+    let is_expected = match frame {
+      Frame::Push(_) => true,
+      ref frame      => frame.is_subscription_confirmation() || frame.is_pubsub_message()
+    };
+
+    if is_expected {
+      Ok(Some(frame))
+    }else{
+      Err(RedisProtocolError::new(
+        RedisProtocolErrorKind::DecodeError,
+        format!("Unexpected {:?} frame on publish-subscribe connection.", frame.kind())
+      ))
+    }
+  }
+
+}
+
+/// Split each complete frame off the front of `buf`, returning the raw bytes of each frame.
+///
+/// Frame boundaries are found with `decode`, so this accepts the same frames, including streamed strings. Any trailing
+/// incomplete frame is left in `buf`.
+pub fn split_frames(buf: &mut BytesMut) -> Result<Vec<Bytes>, RedisProtocolError<'static>> {
+  let mut frames = Vec::new();
+
+  while let (Some(_), len) = decode(buf).map_err(|e| e.into_owned())? {
+    frames.push(buf.split_to(len).freeze());
+  }
+
+  Ok(frames)
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use ::utils;
+  use ::types::*;
+
+  use std::fmt;
+  use std::str;
+  use std::io::Cursor;
+  use std::borrow::Cow;
+
+  use nom::Err as NomError;
+  use nom::simple_errors::Context;
+
+  const PADDING: &'static str = "FOOBARBAZ";
+
+  fn str_to_bytes(s: &str) -> Vec<u8> {
+    s.as_bytes().to_vec()
+  }
+
+  fn to_bytes(s: &str) -> BytesMut {
+    BytesMut::from(str_to_bytes(s))
+  }
+
+  fn empty_bytes() -> BytesMut {
+    BytesMut::new()
+  }
+
+  fn pretty_print_panic(e: RedisProtocolError) {
+    match e.context() {
+      Some(c) => match str::from_utf8(c) {
+        Ok(s) => panic!("Error {:?} with {}", e, s),
+        Err(e) => panic!("{:?}", e)
+      },
+      _ => panic!("{:?}", e)
+    }
+  }
+
+  fn decode_and_verify_some(bytes: &mut BytesMut, expected: &(Option<Frame>, usize)) {
+    let (frame, len) = match decode_bytes(&bytes) {
+      Ok((f, l)) => (f, l),
+      Err(e) => return pretty_print_panic(e)
+    };
+
+    assert_eq!(frame, expected.0, "decoded frame matched");
+    assert_eq!(len, expected.1, "decoded frame len matched");
+  }
+
+  fn decode_and_verify_padded_some(bytes: &mut BytesMut, expected: &(Option<Frame>, usize)) {
+    bytes.extend_from_slice(PADDING.as_bytes());
+
+    let (frame, len) = match decode_bytes(&bytes) {
+      Ok((f, l)) => (f, l),
+      Err(e) => return pretty_print_panic(e)
+    };
+
+    assert_eq!(frame, expected.0, "decoded frame matched");
+    assert_eq!(len, expected.1, "decoded frame len matched");
+  }
+
+  fn decode_and_verify_none(bytes: &mut BytesMut) {
+    let (frame, len) = match decode_bytes(&bytes) {
+      Ok((f, l)) => (f, l),
+      Err(e) => return pretty_print_panic(e)
+    };
+
+    assert!(frame.is_none());
+    assert_eq!(len, 0);
+  }
+
+  #[test]
+  fn should_decode_llen_res_example() {
+    let expected = (Some(Frame::Integer(48293)), 8);
+    let mut bytes: BytesMut = ":48293\r\n".into();
+
+    decode_and_verify_some(&mut bytes, &expected);
+    decode_and_verify_padded_some(&mut bytes, &expected);
+  }
+
+  #[test]
+  fn should_decode_simple_string() {
+    let expected = (Some(Frame::SimpleString("string".into())), 9);
+    let mut bytes: BytesMut = "+string\r\n".into();
+
+    decode_and_verify_some(&mut bytes, &expected);
+    decode_and_verify_padded_some(&mut bytes, &expected);
+  }
+
+  #[test]
+  fn should_decode_bulk_string() {
+    let expected = (Some(Frame::BulkString(str_to_bytes("foo"))), 9);
+    let mut bytes: BytesMut = "$3\r\nfoo\r\n".into();
+
+    decode_and_verify_some(&mut bytes, &expected);
+    decode_and_verify_padded_some(&mut bytes, &expected);
+  }
+
+  #[test]
+  fn should_decode_array_no_nulls() {
+    let expected = (Some(Frame::Array(vec![
+      Frame::SimpleString("Foo".into()),
+      Frame::SimpleString("Bar".into())
+    ])), 16);
+    let mut bytes: BytesMut = "*2\r\n+Foo\r\n+Bar\r\n".into();
+
+    decode_and_verify_some(&mut bytes, &expected);
+    decode_and_verify_padded_some(&mut bytes, &expected);
+  }
+
+  #[test]
+  fn should_decode_array_nulls() {
+    let mut bytes: BytesMut = "*3\r\n$3\r\nFoo\r\n$-1\r\n$3\r\nBar\r\n".into();
+
+    let expected = (Some(Frame::Array(vec![
+      Frame::BulkString(str_to_bytes("Foo")),
+      Frame::Null,
+      Frame::BulkString(str_to_bytes("Bar"))
+    ])), bytes.len());
+
+    decode_and_verify_some(&mut bytes, &expected);
+    decode_and_verify_padded_some(&mut bytes, &expected);
+  }
+
+  #[test]
+  fn should_decode_null() {
+    let expected = (Some(Frame::Null), 5);
+    let mut bytes: BytesMut = "$-1\r\n".into();
+
+    decode_and_verify_some(&mut bytes, &expected);
+    decode_and_verify_padded_some(&mut bytes, &expected);
+  }
+
+  #[test]
+  fn should_decode_resp3_null() {
+    let expected = (Some(Frame::NullResp3), 3);
+    let mut bytes: BytesMut = "_\r\n".into();
+
+    decode_and_verify_some(&mut bytes, &expected);
+    decode_and_verify_padded_some(&mut bytes, &expected);
+  }
+
+  #[test]
+  fn should_distinguish_resp2_and_resp3_nulls() {
+    let (resp2, _) = decode("$-1\r\n".as_bytes()).unwrap();
+    let (resp3, _) = decode("_\r\n".as_bytes()).unwrap();
+
+    assert_ne!(resp2, resp3);
+  }
+
+  #[test]
+  fn should_decode_incomplete_resp3_null() {
+    let mut bytes: BytesMut = "_\r".into();
+    decode_and_verify_none(&mut bytes);
+  }
+
+  #[test]
+  fn should_decode_double() {
+    let expected = (Some(Frame::Double(1.5)), 6);
+    let mut bytes: BytesMut = ",1.5\r\n".into();
+
+    decode_and_verify_some(&mut bytes, &expected);
+    decode_and_verify_padded_some(&mut bytes, &expected);
+  }
+
+  #[test]
+  fn should_decode_whole_number_double() {
+    let expected = (Some(Frame::Double(3.0)), 4);
+    let mut bytes: BytesMut = ",3\r\n".into();
+
+    decode_and_verify_some(&mut bytes, &expected);
+    decode_and_verify_padded_some(&mut bytes, &expected);
+    assert_eq!(decode(b",3.0\r\n").unwrap(), (Some(Frame::Double(3.0)), 6));
+    assert_eq!(decode(b",-12\r\n").unwrap(), (Some(Frame::Double(-12.0)), 6));
+  }
+
+  #[test]
+  fn should_decode_double_inf() {
+    let expected = (Some(Frame::Double(f64::NEG_INFINITY)), 7);
+    let mut bytes: BytesMut = ",-inf\r\n".into();
+
+    decode_and_verify_some(&mut bytes, &expected);
+    decode_and_verify_padded_some(&mut bytes, &expected);
+  }
+
+  #[test]
+  #[should_panic]
+  fn should_error_on_invalid_double() {
+    let bytes: BytesMut = ",foo\r\n".into();
+    let _ = decode_bytes(&bytes).map_err(|e| pretty_print_panic(e));
+  }
+
+  #[test]
+  fn should_decode_bignumber() {
+    let expected = (Some(Frame::BigNumber("-3492890328409238509324850943850943825024385".into())), 47);
+    let mut bytes: BytesMut = "(-3492890328409238509324850943850943825024385\r\n".into();
+
+    decode_and_verify_some(&mut bytes, &expected);
+    decode_and_verify_padded_some(&mut bytes, &expected);
+    assert_eq!(decode_iterative(&bytes).unwrap(), expected);
+  }
+
+  #[test]
+  fn should_error_on_invalid_bignumber() {
+    decode_and_verify_invalid_integer(&"(12a\r\n".into());
+    decode_and_verify_invalid_integer(&"(-\r\n".into());
+  }
+
+  #[test]
+  fn should_error_on_stray_newlines_in_integer() {
+    decode_and_verify_invalid_integer(&":12\r34\r\n".into());
+    decode_and_verify_invalid_integer(&":1\n2\r\n".into());
+
+    assert!(decode_iterative(b":12\r34\r\n").is_err());
+    assert!(decode_view(b":1\n2\r\n").is_err());
+
+    let mut bytes: BytesMut = ":1234\r\n".into();
+    decode_and_verify_some(&mut bytes, &(Some(Frame::Integer(1234)), 7));
+  }
+
+  #[test]
+  fn should_decode_bulkstring_containing_type_bytes() {
+    // the payload is `*2\r\nX`, which is 5 bytes
+    let mut bytes: BytesMut = "$5\r\n*2\r\nX\r\n".into();
+    decode_and_verify_some(&mut bytes, &(Some(Frame::BulkString(str_to_bytes("*2\r\nX"))), 11));
+    decode_and_verify_padded_some(&mut bytes, &(Some(Frame::BulkString(str_to_bytes("*2\r\nX"))), 11));
+
+    let bytes: BytesMut = "$10\r\n+a\r\n-b\r\n:1\r\n:2\r\n".into();
+    let (frames, len) = decode_all(&bytes).unwrap();
+    assert_eq!(frames, vec![Frame::BulkString(str_to_bytes("+a\r\n-b\r\n:1")), Frame::Integer(2)]);
+    assert_eq!(len, bytes.len());
+  }
+
+  #[test]
+  fn should_decode_scalar_integer() {
+    assert_eq!(decode_scalar(b":42\r\n").unwrap(), (Some(ScalarFrame::Int(42)), 5));
+    assert_eq!(decode_scalar(b":-1\r\n+OK\r\n").unwrap(), (Some(ScalarFrame::Int(-1)), 5));
+    assert_eq!(decode_scalar(b":42\r").unwrap(), (None, 0));
+  }
+
+  #[test]
+  fn should_decode_scalar_status_and_null() {
+    assert_eq!(decode_scalar(b"+OK\r\n").unwrap(), (Some(ScalarFrame::StatusLen(2)), 5));
+    assert_eq!(decode_scalar(b"-ERR foo\r\n").unwrap(), (Some(ScalarFrame::Error), 10));
+    assert_eq!(decode_scalar(b"$-1\r\n").unwrap(), (Some(ScalarFrame::Null), 5));
+    assert_eq!(decode_scalar(b"_\r\n").unwrap(), (Some(ScalarFrame::Null), 3));
+  }
+
+  #[test]
+  fn should_error_decoding_aggregate_as_scalar() {
+    let e = decode_scalar(b"*1\r\n:1\r\n").unwrap_err();
+    assert_eq!(e.kind(), &RedisProtocolErrorKind::DecodeError);
+    assert_eq!(e.to_string(), "Decode Error: Expected scalar frame.");
+
+    assert!(decode_scalar(b"$3\r\nfoo\r\n").is_err());
+    assert!(decode_scalar(b"%1\r\n:1\r\n:2\r\n").is_err());
+  }
+
+  #[test]
+  fn should_error_on_duplicate_set_elements_when_strict() {
+    let bytes = b"~2\r\n$1\r\na\r\n$1\r\na\r\n";
+
+    let expected = Frame::Set(vec![Frame::BulkString(str_to_bytes("a")), Frame::BulkString(str_to_bytes("a"))]);
+    assert_eq!(decode(bytes).unwrap(), (Some(expected), 18));
+
+    let e = decode_strict(bytes).unwrap_err();
+    assert_eq!(e.kind(), &RedisProtocolErrorKind::DecodeError);
+    assert_eq!(e.to_string(), "Decode Error: Duplicate set element.");
+  }
+
+  #[test]
+  fn should_decode_unique_set_elements_when_strict() {
+    let bytes = b"~2\r\n$1\r\na\r\n+a\r\n";
+    let expected = Frame::Set(vec![Frame::BulkString(str_to_bytes("a")), Frame::SimpleString("a".into())]);
+
+    assert_eq!(decode_strict(bytes).unwrap(), (Some(expected), 15));
+    assert_eq!(decode_strict(b"~2\r\n$1\r\na\r\n$1\r\na").unwrap(), (None, 0));
+    assert!(decode_strict(b"*2\r\n$1\r\na\r\n$1\r\na\r\n").unwrap().0.is_some());
+  }
+
+  #[test]
+  fn should_decode_signed_zero_set_elements_when_strict() {
+    let bytes = b"~2\r\n,0\r\n,-0\r\n";
+    let expected = Frame::Set(vec![Frame::Double(0.0), Frame::Double(-0.0)]);
+
+    assert_eq!(decode_strict(bytes).unwrap(), (Some(expected), bytes.len()));
+  }
+
+  #[test]
+  fn should_detect_resp2_version() {
+    let (frame, len) = decode_detect_version(b"*3\r\n$3\r\nfoo\r\n:1\r\n*1\r\n$-1\r\n").unwrap();
+    let expected = Frame::Array(vec![
+      Frame::BulkString(str_to_bytes("foo")),
+      Frame::Integer(1),
+      Frame::Array(vec![Frame::Null])
+    ]);
+
+    assert_eq!(frame, Some((expected, RespVersion::Resp2)));
+    assert_eq!(len, 26);
+    assert_eq!(decode_detect_version(b"+OK\r\n").unwrap(), (Some((Frame::SimpleString("OK".into()), RespVersion::Resp2)), 5));
+  }
+
+  #[test]
+  fn should_detect_resp3_version() {
+    let (frame, _) = decode_detect_version(b"%1\r\n+foo\r\n:1\r\n").unwrap();
+    assert_eq!(frame.unwrap().1, RespVersion::Resp3);
+
+    let (frame, _) = decode_detect_version(b"*2\r\n:1\r\n*1\r\n_\r\n").unwrap();
+    assert_eq!(frame.unwrap().1, RespVersion::Resp3);
+
+    assert_eq!(decode_detect_version(b"%1\r\n+foo\r\n").unwrap(), (None, 0));
+  }
+
+  #[test]
+  fn should_decode_expecting_integer() {
+    assert_eq!(decode_expecting(b":42\r\n", ReplyShape::Integer).unwrap(), (Some(Frame::Integer(42)), 5));
+    assert_eq!(decode_expecting(b":42\r", ReplyShape::Integer).unwrap(), (None, 0));
+
+    let e = decode_expecting(b"$3\r\nfoo\r\n", ReplyShape::Integer).unwrap_err();
+    assert_eq!(e.kind(), &RedisProtocolErrorKind::DecodeError);
+    assert_eq!(e.to_string(), "Decode Error: Expected Integer reply, found BulkString.");
+  }
+
+  #[test]
+  fn should_decode_expecting_bulk_or_null() {
+    assert_eq!(decode_expecting(b"$3\r\nfoo\r\n", ReplyShape::BulkOrNull).unwrap(), (Some(Frame::BulkString(str_to_bytes("foo"))), 9));
+    assert_eq!(decode_expecting(b"$-1\r\n", ReplyShape::BulkOrNull).unwrap(), (Some(Frame::Null), 5));
+    assert!(decode_expecting(b"+OK\r\n", ReplyShape::BulkOrNull).is_err());
+    assert!(decode_expecting(b"*0\r\n", ReplyShape::Status).is_err());
+  }
+
+  #[test]
+  fn should_decode_expecting_array_or_null() {
+    let expected = Frame::Array(vec![Frame::BulkString(str_to_bytes("foo"))]);
+
+    assert_eq!(decode_expecting(b"*1\r\n$3\r\nfoo\r\n", ReplyShape::Array).unwrap(), (Some(expected), 13));
+    assert_eq!(decode_expecting(b"*-1\r\n", ReplyShape::Array).unwrap(), (Some(Frame::Null), 5));
+    assert_eq!(decode_expecting(b"_\r\n", ReplyShape::Array).unwrap(), (Some(Frame::NullResp3), 3));
+
+    let e = decode_expecting(b":1\r\n", ReplyShape::Array).unwrap_err();
+    assert_eq!(e.to_string(), "Decode Error: Expected Array reply, found Integer.");
+  }
+
+  #[test]
+  fn should_pass_errors_when_decoding_expecting() {
+    let expected = (Some(Frame::Error("ERR foo".into())), 10);
+
+    assert_eq!(decode_expecting(b"-ERR foo\r\n", ReplyShape::Integer).unwrap(), expected);
+    assert_eq!(decode_expecting(b"-ERR foo\r\n", ReplyShape::BulkOrNull).unwrap(), expected);
+    assert_eq!(decode_expecting(b"-ERR foo\r\n", ReplyShape::Status).unwrap(), expected);
+    assert_eq!(decode_expecting(b"-ERR foo\r\n", ReplyShape::Array).unwrap(), expected);
+  }
+
+  #[test]
+  fn should_decode_all_many_pipelined_frames() {
+    let count = 10_000;
+    let mut bytes = ":1\r\n".repeat(count).into_bytes();
+    bytes.extend_from_slice(b":1\r");
+
+    let (frames, len) = decode_all(&bytes).unwrap();
+    assert_eq!(frames.len(), count);
+    assert!(frames.iter().all(|f| *f == Frame::Integer(1)));
+    assert_eq!(len, count * 4);
+  }
+
+  #[test]
+  fn should_decode_empty_simplestring_and_error() {
+    let mut bytes: BytesMut = "+\r\n".into();
+    decode_and_verify_some(&mut bytes, &(Some(Frame::SimpleString(String::new())), 3));
+    decode_and_verify_padded_some(&mut bytes, &(Some(Frame::SimpleString(String::new())), 3));
+
+    let mut bytes: BytesMut = "-\r\n".into();
+    decode_and_verify_some(&mut bytes, &(Some(Frame::Error(String::new())), 3));
+    decode_and_verify_padded_some(&mut bytes, &(Some(Frame::Error(String::new())), 3));
+
+    assert_eq!(decode_iterative(b"+\r\n").unwrap(), (Some(Frame::SimpleString(String::new())), 3));
+    assert_eq!(decode_all(b"+\r\n-\r\n").unwrap(), (vec![Frame::SimpleString(String::new()), Frame::Error(String::new())], 6));
+  }
+
+  #[test]
+  fn should_decode_verbatimstring_txt() {
+    let expected = Frame::VerbatimString { format: VerbatimFormat::Txt, data: str_to_bytes("Some string") };
+    let mut bytes: BytesMut = "=15\r\ntxt:Some string\r\n".into();
+
+    decode_and_verify_some(&mut bytes, &(Some(expected.clone()), 22));
+    decode_and_verify_padded_some(&mut bytes, &(Some(expected.clone()), 22));
+    assert_eq!(expected.verbatim_format(), Some(VerbatimFormat::Txt));
+  }
+
+  #[test]
+  fn should_decode_verbatimstring_markdown_and_other() {
+    let (frame, _) = decode(b"=6\r\nmkd:# \r\n").unwrap();
+    assert_eq!(frame.unwrap().verbatim_format(), Some(VerbatimFormat::Markdown));
+
+    let (frame, len) = decode(b"=7\r\nabc:foo\r\n").unwrap();
+    let expected = Frame::VerbatimString { format: VerbatimFormat::Other(*b"abc"), data: str_to_bytes("foo") };
+    assert_eq!(frame, Some(expected.clone()));
+    assert_eq!(len, 13);
+    assert_eq!(decode_iterative(b"=7\r\nabc:foo\r\n").unwrap(), (Some(expected), 13));
+    assert_eq!(Frame::BulkString(str_to_bytes("foo")).verbatim_format(), None);
+  }
+
+  #[test]
+  fn should_error_on_invalid_verbatimstring_format() {
+    let e = decode(b"=3\r\ntxt\r\n").unwrap_err();
+    assert_eq!(e.kind(), &RedisProtocolErrorKind::DecodeError);
+    assert_eq!(e.to_string(), "Decode Error: Invalid verbatim string format.");
+
+    assert!(decode(b"=7\r\ntxt-foo\r\n").is_err());
+  }
+
+  #[test]
+  fn should_detect_valid_frame_start() {
+    for byte in b"+-:$*_,#(=%~>|".iter() {
+      assert!(is_valid_frame_start(*byte), "{} is a valid frame start", *byte as char);
+    }
+  }
+
+  #[test]
+  fn should_reject_invalid_frame_start() {
+    for byte in [b'x', b'\0', b';', b'\r', b'G'].iter() {
+      assert!(!is_valid_frame_start(*byte), "{:?} is not a valid frame start", *byte as char);
+    }
+  }
+
+  #[test]
+  fn should_decode_boolean() {
+    let mut bytes: BytesMut = "#t\r\n".into();
+    decode_and_verify_some(&mut bytes, &(Some(Frame::Boolean(true)), 4));
+    decode_and_verify_padded_some(&mut bytes, &(Some(Frame::Boolean(true)), 4));
+
+    let mut bytes: BytesMut = "#f\r\n".into();
+    decode_and_verify_some(&mut bytes, &(Some(Frame::Boolean(false)), 4));
+    decode_and_verify_padded_some(&mut bytes, &(Some(Frame::Boolean(false)), 4));
+  }
+
+  #[test]
+  fn should_decode_incomplete_boolean() {
+    let mut bytes: BytesMut = "#t\r".into();
+    decode_and_verify_none(&mut bytes);
+  }
+
+  #[test]
+  #[should_panic]
+  fn should_error_on_invalid_boolean() {
+    let bytes: BytesMut = "#x\r\n".into();
+    let _ = decode_bytes(&bytes).map_err(|e| pretty_print_panic(e));
+  }
+
+  #[test]
+  fn should_decode_mixed_resp2_and_resp3_frames() {
+    let bytes: BytesMut = "$3\r\nfoo\r\n$-1\r\n_\r\n#t\r\n".into();
+
+    let (frame, len) = decode(&bytes).unwrap();
+    assert_eq!(frame, Some(Frame::BulkString(str_to_bytes("foo"))));
+    assert_eq!(len, 9);
+    let (frame, len) = decode(&bytes[9..]).unwrap();
+    assert_eq!(frame, Some(Frame::Null));
+    assert_eq!(len, 5);
+    let (frame, len) = decode(&bytes[14..]).unwrap();
+    assert_eq!(frame, Some(Frame::NullResp3));
+    assert_eq!(len, 3);
+    let (frame, len) = decode(&bytes[17..]).unwrap();
+    assert_eq!(frame, Some(Frame::Boolean(true)));
+    assert_eq!(len, 4);
+
+    let (frames, len) = decode_all(&bytes).unwrap();
+    assert_eq!(frames, vec![
+      Frame::BulkString(str_to_bytes("foo")),
+      Frame::Null,
+      Frame::NullResp3,
+      Frame::Boolean(true)
+    ]);
+    assert_eq!(len, bytes.len());
+
+    let mut split = bytes.clone();
+    let chunks = split_frames(&mut split).unwrap();
+    let lens: Vec<usize> = chunks.iter().map(|c| c.len()).collect();
+    assert_eq!(lens, vec![9, 5, 3, 4]);
+    assert!(split.is_empty());
+  }
+
+  #[test]
+  fn should_decode_all_with_interleaved_push() {
+    let bytes: BytesMut = ">3\r\n$7\r\nmessage\r\n$3\r\nfoo\r\n$3\r\nbar\r\n:1\r\n".into();
+
+    let (frames, len) = decode_all(&bytes).unwrap();
+    assert_eq!(len, bytes.len());
+    assert_eq!(frames.len(), 2);
+
+    assert!(frames[0].is_push());
+    assert_eq!(frames[0], Frame::Push(vec![
+      Frame::BulkString(str_to_bytes("message")),
+      Frame::BulkString(str_to_bytes("foo")),
+      Frame::BulkString(str_to_bytes("bar"))
+    ]));
+    assert!(!frames[1].is_push());
+    assert_eq!(frames[1], Frame::Integer(1));
+  }
+
+  #[test]
+  fn should_decode_all_with_trailing_incomplete_frame() {
+    let bytes: BytesMut = ":1\r\n:2\r\n$3\r\nfo".into();
+
+    let (frames, len) = decode_all(&bytes).unwrap();
+    assert_eq!(frames, vec![Frame::Integer(1), Frame::Integer(2)]);
+    assert_eq!(len, 8);
+  }
+
+  #[test]
+  fn should_not_consume_partial_array_until_complete() {
+    let mut bytes: BytesMut = "*2\r\n:1\r\n:".into();
+
+    assert_eq!(decode_bytes(&bytes).unwrap(), (None, 0));
+    assert_eq!(decode_iterative(&bytes).unwrap(), (None, 0));
+
+    bytes.extend_from_slice(b"2\r\n+OK\r\n");
+    let expected = (Some(Frame::Array(vec![Frame::Integer(1), Frame::Integer(2)])), 12);
+
+    assert_eq!(decode_bytes(&bytes).unwrap(), expected);
+    assert_eq!(decode_iterative(&bytes).unwrap(), expected);
+
+    let _ = bytes.split_to(12);
+    assert_eq!(decode_bytes(&bytes).unwrap(), (Some(Frame::SimpleString("OK".into())), 5));
+  }
+
+  #[test]
+  fn should_leave_partial_tail_after_pipelined_frame() {
+    let mut bytes: BytesMut = ":1\r\n:2".into();
+
+    assert_eq!(decode_bytes(&bytes).unwrap(), (Some(Frame::Integer(1)), 4));
+    let _ = bytes.split_to(4);
+    assert_eq!(&bytes[..], b":2");
+
+    assert_eq!(decode_bytes(&bytes).unwrap(), (None, 0));
+    bytes.extend_from_slice(b"\r");
+    assert_eq!(decode_bytes(&bytes).unwrap(), (None, 0));
+    bytes.extend_from_slice(b"\n");
+    assert_eq!(decode_bytes(&bytes).unwrap(), (Some(Frame::Integer(2)), 4));
+  }
+
+  #[test]
+  fn should_leave_partial_tail_decoding_all() {
+    let mut bytes: BytesMut = ":1\r\n:2".into();
+
+    assert_eq!(decode_all(&bytes).unwrap(), (vec![Frame::Integer(1)], 4));
+    assert_eq!(split_frames(&mut bytes).unwrap(), vec![Bytes::from(":1\r\n")]);
+    assert_eq!(&bytes[..], b":2");
+
+    assert_eq!(decode_all(&bytes).unwrap(), (vec![], 0));
+    bytes.extend_from_slice(b"\r\n:3\r\n");
+    assert_eq!(decode_all(&bytes).unwrap(), (vec![Frame::Integer(2), Frame::Integer(3)], 8));
+  }
+
+  #[test]
+  fn should_decode_frames_from_cursor() {
+    let bytes = b"+OK\r\n:42\r\n$3\r\nfoo\r\n$3\r\nba";
+    let mut cursor = Cursor::new(&bytes[..]);
+
+    assert_eq!(decode_cursor(&mut cursor).unwrap(), Some(Frame::SimpleString("OK".into())));
+    assert_eq!(cursor.position(), 5);
+    assert_eq!(decode_cursor(&mut cursor).unwrap(), Some(Frame::Integer(42)));
+    assert_eq!(cursor.position(), 10);
+    assert_eq!(decode_cursor(&mut cursor).unwrap(), Some(Frame::BulkString(str_to_bytes("foo"))));
+    assert_eq!(cursor.position(), 19);
+
+    assert_eq!(decode_cursor(&mut cursor).unwrap(), None);
+    assert_eq!(cursor.position(), 19);
+  }
+
+  #[test]
+  fn should_decode_streamed_string() {
+    let mut bytes: BytesMut = "$?\r\n;3\r\nfoo\r\n;4\r\nbar!\r\n;0\r\n".into();
+    let expected = (Some(Frame::BulkString(str_to_bytes("foobar!"))), bytes.len());
+
+    decode_and_verify_some(&mut bytes, &expected);
+    decode_and_verify_padded_some(&mut bytes, &expected);
+  }
+
+  #[test]
+  fn should_decode_empty_streamed_string() {
+    let mut bytes: BytesMut = "$?\r\n;0\r\n".into();
+    decode_and_verify_some(&mut bytes, &(Some(Frame::BulkString(vec![])), 8));
+  }
+
+  #[test]
+  fn should_decode_incomplete_streamed_string() {
+    let mut bytes: BytesMut = "$?\r\n;3\r\nfoo\r\n;".into();
+    decode_and_verify_none(&mut bytes);
+
+    let mut bytes: BytesMut = "$?\r\n;3\r\nfo".into();
+    decode_and_verify_none(&mut bytes);
+  }
+
+  #[test]
+  fn should_decode_streamed_string_in_array() {
+    let mut bytes: BytesMut = "*2\r\n$?\r\n;3\r\nfoo\r\n;0\r\n:1\r\n".into();
+    let expected = (Some(Frame::Array(vec![Frame::BulkString(str_to_bytes("foo")), Frame::Integer(1)])), bytes.len());
+
+    decode_and_verify_some(&mut bytes, &expected);
+  }
+
+  #[test]
+  fn should_decode_streamed_array() {
+    let mut bytes: BytesMut = "*?\r\n:1\r\n$3\r\nfoo\r\n.\r\n".into();
+    let expected = (Some(Frame::Array(vec![Frame::Integer(1), Frame::BulkString(str_to_bytes("foo"))])), 20);
+
+    decode_and_verify_some(&mut bytes, &expected);
+    decode_and_verify_padded_some(&mut bytes, &expected);
+  }
+
+  #[test]
+  fn should_decode_empty_and_nested_streamed_arrays() {
+    let mut bytes: BytesMut = "*?\r\n.\r\n".into();
+    decode_and_verify_some(&mut bytes, &(Some(Frame::Array(vec![])), 7));
+
+    let mut bytes: BytesMut = "*?\r\n*?\r\n:1\r\n.\r\n.\r\n".into();
+    let expected = (Some(Frame::Array(vec![Frame::Array(vec![Frame::Integer(1)])])), bytes.len());
+    decode_and_verify_some(&mut bytes, &expected);
+  }
+
+  #[test]
+  fn should_decode_incomplete_streamed_array() {
+    decode_and_verify_none(&mut "*?\r\n:1\r\n".into());
+    decode_and_verify_none(&mut "*?\r\n:1\r\n.".into());
+    decode_and_verify_none(&mut "*?\r\n:1\r\n.\r".into());
+  }
+
+  #[test]
+  fn should_error_on_invalid_streamed_array_terminator() {
+    let e = decode(b"*?\r\n:1\r\n:2\r\n.x\r\n").unwrap_err();
+    assert_eq!(e.kind(), &RedisProtocolErrorKind::DecodeError);
+    assert_eq!(e.to_string(), "Decode Error: Invalid streamed aggregate terminator.");
+
+    assert!(decode(b"*?\r\n.foo\r\n").is_err());
+  }
+
+  #[test]
+  fn should_decode_streamed_array_view() {
+    let bytes = b"*?\r\n:1\r\n$3\r\nfoo\r\n*?\r\n.\r\n.\r\n";
+    let (view, len) = decode_view(bytes).unwrap();
+    let expected = Frame::Array(vec![
+      Frame::Integer(1),
+      Frame::BulkString(str_to_bytes("foo")),
+      Frame::Array(vec![])
+    ]);
+
+    assert_eq!(len, bytes.len());
+    assert_eq!(view.unwrap().to_owned().unwrap(), expected);
+    assert_eq!(decode_view(b"*?\r\n:1\r\n.").unwrap(), (None, 0));
+    assert!(decode_view(b"*?\r\n:1\r\n.x\r\n").is_err());
+  }
+
+  #[test]
+  fn should_split_streamed_array_frames() {
+    let mut buf: BytesMut = "*?\r\n:1\r\n.\r\n+OK\r\n*?\r\n:2".into();
+    let frames = split_frames(&mut buf).unwrap();
+
+    assert_eq!(frames, vec![Bytes::from("*?\r\n:1\r\n.\r\n"), Bytes::from("+OK\r\n")]);
+    assert_eq!(&buf[..], b"*?\r\n:2");
+  }
+
+  #[test]
+  fn should_split_streamed_string_frames() {
+    let mut buf: BytesMut = "$?\r\n;3\r\nfoo\r\n;0\r\n:1\r\n$?\r\n;3\r\nba".into();
+    let frames = split_frames(&mut buf).unwrap();
+
+    assert_eq!(frames, vec![Bytes::from("$?\r\n;3\r\nfoo\r\n;0\r\n"), Bytes::from(":1\r\n")]);
+    assert_eq!(&buf[..], b"$?\r\n;3\r\nba");
+  }
+
+  #[test]
+  fn should_decode_streamed_array_iteratively() {
+    let bytes = b"*?\r\n:1\r\n*?\r\n*1\r\n+OK\r\n.\r\n%1\r\n+a\r\n*?\r\n.\r\n.\r\n";
+
+    assert_eq!(decode_iterative(bytes).unwrap(), decode(bytes).unwrap());
+    assert_eq!(decode_iterative(b"*?\r\n:1\r\n.").unwrap(), (None, 0));
+
+    let e = decode_iterative(b"*?\r\n:1\r\n.x\r\n").unwrap_err();
+    assert_eq!(e.to_string(), "Decode Error: Invalid streamed aggregate terminator.");
+  }
+
+  #[test]
+  fn should_decode_map() {
+    let mut bytes: BytesMut = "%2\r\n+foo\r\n:1\r\n$3\r\nbar\r\n*1\r\n#t\r\n".into();
+    let expected = (Some(Frame::Map(vec![
+      (Frame::SimpleString("foo".into()), Frame::Integer(1)),
+      (Frame::BulkString(str_to_bytes("bar")), Frame::Array(vec![Frame::Boolean(true)]))
+    ])), bytes.len());
+
+    decode_and_verify_some(&mut bytes, &expected);
+    decode_and_verify_padded_some(&mut bytes, &expected);
+  }
+
+  #[test]
+  fn should_decode_set_push_and_attribute() {
+    let mut bytes: BytesMut = "~2\r\n:1\r\n:2\r\n".into();
+    decode_and_verify_some(&mut bytes, &(Some(Frame::Set(vec![Frame::Integer(1), Frame::Integer(2)])), 12));
+
+    let mut bytes: BytesMut = ">2\r\n+message\r\n$3\r\nfoo\r\n".into();
+    let expected = (Some(Frame::Push(vec![Frame::SimpleString("message".into()), Frame::BulkString(str_to_bytes("foo"))])), 23);
+    decode_and_verify_some(&mut bytes, &expected);
+
+    let mut bytes: BytesMut = "|1\r\n+ttl\r\n:3600\r\n".into();
+    let expected = (Some(Frame::Attribute(vec![(Frame::SimpleString("ttl".into()), Frame::Integer(3600))])), 17);
+    decode_and_verify_some(&mut bytes, &expected);
+  }
+
+  #[test]
+  fn should_decode_incomplete_map() {
+    let mut bytes: BytesMut = "%1\r\n+foo\r\n".into();
+    decode_and_verify_none(&mut bytes);
+  }
+
+  // nests an integer `depth` levels deep, where `prefix` is the header of one level of the aggregate
+  fn nested(prefix: &str, depth: usize) -> BytesMut {
+    let mut bytes = BytesMut::new();
+    for _ in 0..depth {
+      bytes.extend_from_slice(prefix.as_bytes());
+    }
+    bytes.extend_from_slice(b":1\r\n");
+
+    bytes
+  }
+
+  #[test]
+  fn should_decode_nested_aggregates_within_max_depth() {
+    let options = DecodeOptions {
+      max_depth: Some(3),
+      ..Default::default()
+    };
+
+    for prefix in ["*1\r\n", "%1\r\n:1\r\n", "~1\r\n", ">1\r\n", "|1\r\n:1\r\n"].iter() {
+      let bytes = nested(prefix, 3);
+      let (frame, len) = decode_bytes_with_options(&bytes, &options).unwrap();
+      assert!(frame.is_some());
+      assert_eq!(len, bytes.len());
+    }
+  }
+
+  #[test]
+  fn should_error_on_nested_aggregates_past_max_depth() {
+    let options = DecodeOptions {
+      max_depth: Some(3),
+      ..Default::default()
+    };
+
+    for prefix in ["*1\r\n", "%1\r\n:1\r\n", "~1\r\n", ">1\r\n", "|1\r\n:1\r\n"].iter() {
+      let bytes = nested(prefix, 4);
+      let e = decode_bytes_with_options(&bytes, &options).unwrap_err();
+      assert_eq!(e.kind(), &RedisProtocolErrorKind::DecodeError);
+      assert_eq!(e.to_string(), "Decode Error: Max depth exceeded.");
+    }
+  }
+
+  #[test]
+  fn should_error_on_nested_view_past_max_depth() {
+    let options = DecodeOptions {
+      max_depth: Some(3),
+      ..Default::default()
+    };
+
+    for prefix in ["*1\r\n", "%1\r\n:1\r\n", "~1\r\n", ">1\r\n", "|1\r\n:1\r\n"].iter() {
+      let bytes = nested(prefix, 3);
+      let (view, len) = decode_view_with_options(&bytes, &options).unwrap();
+      assert!(view.is_some());
+      assert_eq!(len, bytes.len());
+
+      let bytes = nested(prefix, 4);
+      let e = decode_view_with_options(&bytes, &options).unwrap_err();
+      assert_eq!(e.to_string(), "Decode Error: Max depth exceeded.");
+    }
+  }
+
+  #[test]
+  fn should_error_on_deeply_nested_view_without_overflowing() {
+    let options = DecodeOptions {
+      max_depth: Some(32),
+      ..Default::default()
+    };
+    let bytes = "*1\r\n".repeat(1024 * 1024);
+
+    let e = decode_view_with_options(bytes.as_bytes(), &options).unwrap_err();
+    assert_eq!(e.to_string(), "Decode Error: Max depth exceeded.");
+  }
+
+  #[test]
+  fn should_error_on_view_line_longer_than_max_line_len() {
+    let options = DecodeOptions {
+      max_line_len: Some(6),
+      ..Default::default()
+    };
+
+    assert_eq!(decode_view_with_options(b"+foobar\r\n", &options).unwrap(), (Some(FrameView::SimpleString(b"foobar")), 9));
+
+    let e = decode_view_with_options(b"+foobarbaz", &options).unwrap_err();
+    assert_eq!(e.to_string(), "Decode Error: Line too long.");
+  }
+
+  #[test]
+  fn should_count_mixed_aggregates_towards_max_depth() {
+    let options = DecodeOptions {
+      max_depth: Some(3),
+      ..Default::default()
+    };
+
+    let bytes: BytesMut = "*1\r\n%1\r\n:1\r\n~1\r\n>1\r\n:1\r\n".into();
+    let e = decode_bytes_with_options(&bytes, &options).unwrap_err();
+    assert_eq!(e.to_string(), "Decode Error: Max depth exceeded.");
+
+    let bytes: BytesMut = "*1\r\n%1\r\n:1\r\n~1\r\n:1\r\n".into();
+    let (frame, _) = decode_bytes_with_options(&bytes, &options).unwrap();
+    assert!(frame.is_some());
+  }
+
+  #[test]
+  fn should_decode_iterative_same_as_recursive() {
+    let inputs = [
+      "+OK\r\n",
+      "$-1\r\n",
+      "*-1\r\n",
+      "*0\r\n",
+      "*3\r\n$3\r\nFoo\r\n$-1\r\n$3\r\nBar\r\n",
+      "*2\r\n*2\r\n:1\r\n:2\r\n*0\r\n",
+      "%2\r\n+foo\r\n~1\r\n:1\r\n*1\r\n#t\r\n>1\r\n_\r\n",
+      "|1\r\n+ttl\r\n:3600\r\n",
+      "*2\r\n$?\r\n;3\r\nfoo\r\n;0\r\n,1.5\r\nFOOBAR"
+    ];
+
+    for input in inputs.iter() {
+      assert_eq!(decode_iterative(input.as_bytes()).unwrap(), decode(input.as_bytes()).unwrap());
+    }
+  }
+
+  #[test]
+  fn should_decode_iterative_incomplete() {
+    let (frame, len) = decode_iterative(b"*2\r\n*2\r\n:1\r\n:2\r\n").unwrap();
+    assert!(frame.is_none());
+    assert_eq!(len, 0);
+  }
+
+  #[test]
+  fn should_error_iterative_on_invalid_frame() {
+    let e = decode_iterative(b"*2\r\n:1\r\n:-\r\n").unwrap_err();
+    assert_eq!(e.to_string(), "Decode Error: Invalid integer.");
+  }
+
+  #[test]
+  fn should_decode_iterative_deeply_nested_array() {
+    let depth = 5000;
+    let mut bytes = BytesMut::new();
+    for _ in 0..depth {
+      bytes.extend_from_slice(b"*1\r\n");
+    }
+    bytes.extend_from_slice(b":1\r\n");
+
+    let (frame, len) = decode_iterative(&bytes).unwrap();
+    assert_eq!(len, bytes.len());
+
+    let mut frame = frame.unwrap();
+    for _ in 0..depth {
+      frame = match frame {
+        Frame::Array(mut frames) => frames.pop().unwrap(),
+        f => panic!("Expected array, found {:?}", f)
+      };
+    }
+    assert_eq!(frame, Frame::Integer(1));
+  }
+
+  #[test]
+  fn should_decode_from_reader_across_reads() {
+    let mut reader = Cursor::new(b"*2\r\n$3\r\nfoo".to_vec()).chain(Cursor::new(b"\r\n:1\r\n+OK\r\n".to_vec()));
+    let mut buf = BytesMut::new();
+
+    let frame = decode_from_reader(&mut reader, &mut buf).unwrap();
+    assert_eq!(frame, Frame::Array(vec![Frame::BulkString(str_to_bytes("foo")), Frame::Integer(1)]));
+    assert_eq!(&buf[..], b"+OK\r\n");
+
+    let frame = decode_from_reader(&mut reader, &mut buf).unwrap();
+    assert_eq!(frame, Frame::SimpleString("OK".into()));
+    assert!(buf.is_empty());
+  }
+
+  #[test]
+  fn should_error_on_eof_from_reader() {
+    let mut reader = Cursor::new(b"$3\r\nfo".to_vec());
+    let mut buf = BytesMut::new();
+
+    let e = decode_from_reader(&mut reader, &mut buf).unwrap_err();
+    assert_eq!(e.kind(), &RedisProtocolErrorKind::DecodeError);
+    assert_eq!(e.to_string(), "Decode Error: Unexpected EOF.");
+    assert_eq!(&buf[..], b"$3\r\nfo");
+  }
+
+  #[test]
+  fn should_hint_remaining_bulk_string_bytes() {
+    assert_eq!(decode_hint(b"$10\r\nfoo").unwrap(), DecodeHint::NeedMore(Some(9)));
+    assert_eq!(decode_hint(b"$10\r\n").unwrap(), DecodeHint::NeedMore(Some(12)));
+    assert_eq!(decode_hint(b"$3\r\nfoo\r").unwrap(), DecodeHint::NeedMore(Some(1)));
+  }
+
+  #[test]
+  fn should_hint_unknown_remaining_bytes() {
+    assert_eq!(decode_hint(b"").unwrap(), DecodeHint::NeedMore(None));
+    assert_eq!(decode_hint(b"$10").unwrap(), DecodeHint::NeedMore(None));
+    assert_eq!(decode_hint(b"*2\r\n$3\r\nfoo\r\n").unwrap(), DecodeHint::NeedMore(None));
+    assert_eq!(decode_hint(b"$?\r\n;3\r\nfoo\r\n").unwrap(), DecodeHint::NeedMore(None));
+  }
+
+  #[test]
+  fn should_hint_complete_frame() {
+    let hint = decode_hint(b"$3\r\nfoo\r\n:1\r\n").unwrap();
+    assert_eq!(hint, DecodeHint::Complete(Frame::BulkString(str_to_bytes("foo")), 9));
+  }
+
+  #[test]
+  fn should_decode_inline_command() {
+    let (frame, len) = decode_inline(b"PING\r\n").unwrap();
+    assert_eq!(frame, Some(Frame::Array(vec![Frame::BulkString(str_to_bytes("PING"))])));
+    assert_eq!(len, 6);
+  }
+
+  #[test]
+  fn should_decode_inline_command_with_extra_spaces() {
+    let (frame, len) = decode_inline(b"PING  hello \r\n").unwrap();
+    assert_eq!(frame, Some(Frame::Array(vec![Frame::BulkString(str_to_bytes("PING")), Frame::BulkString(str_to_bytes("hello"))])));
+    assert_eq!(len, 14);
+
+    let (frame, len) = decode_inline(b" \tSET foo   bar\nPING\r\n").unwrap();
+    assert_eq!(frame, Some(Frame::Array(vec![
+      Frame::BulkString(str_to_bytes("SET")),
+      Frame::BulkString(str_to_bytes("foo")),
+      Frame::BulkString(str_to_bytes("bar"))
+    ])));
+    assert_eq!(len, 16);
+  }
+
+  #[test]
+  fn should_decode_empty_inline_command() {
+    let (frame, len) = decode_inline(b"\r\n").unwrap();
+    assert_eq!(frame, Some(Frame::Array(vec![])));
+    assert_eq!(len, 2);
+  }
+
+  #[test]
+  fn should_decode_incomplete_inline_command() {
+    let (frame, len) = decode_inline(b"PING hel").unwrap();
+    assert!(frame.is_none());
+    assert_eq!(len, 0);
+  }
+
+  #[test]
+  fn should_decode_streaming_large_bulk_string() {
+    let expected: Vec<u8> = (0..3 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+    let mut encoded = BytesMut::from(format!("${}\r\n", expected.len()).as_bytes());
+    encoded.extend_from_slice(&expected);
+    encoded.extend_from_slice(b"\r\n:1\r\n");
+
+    let mut chunks = encoded.chunks(64 * 1024);
+    let mut buf = BytesMut::new();
+    let mut actual = Vec::new();
+
+    let mut continuation = loop {
+      buf.extend_from_slice(chunks.next().unwrap());
+
+      match decode_streaming(&mut buf, 1024 * 1024).unwrap() {
+        Some(StreamingFrame::BulkStringStreaming { total_len, available, continuation }) => {
+          assert_eq!(total_len, expected.len());
+          actual.extend_from_slice(&available);
+          break continuation;
+        },
+        Some(f) => panic!("Expected streaming bulk string, found {:?}", f),
+        None => continue
+      }
+    };
+
+    while !continuation.is_finished() {
+      buf.extend_from_slice(chunks.next().unwrap());
+
+      while let Some(chunk) = continuation.next_chunk(&mut buf).unwrap() {
+        actual.extend_from_slice(&chunk);
+      }
+    }
+
+    assert_eq!(continuation.received(), expected.len());
+    assert!(actual == expected);
+    assert_eq!(&buf[..], b":1\r\n");
+    assert_eq!(decode_streaming(&mut buf, 1024 * 1024).unwrap(), Some(StreamingFrame::Complete(Frame::Integer(1))));
+    assert!(buf.is_empty());
+  }
+
+  #[test]
+  fn should_decode_streaming_small_frames() {
+    let mut buf: BytesMut = "$3\r\nfoo\r\n$3\r\nba".into();
+
+    let frame = decode_streaming(&mut buf, 1024).unwrap();
+    assert_eq!(frame, Some(StreamingFrame::Complete(Frame::BulkString(str_to_bytes("foo")))));
+    assert_eq!(decode_streaming(&mut buf, 1024).unwrap(), None);
+    assert_eq!(&buf[..], b"$3\r\nba");
+  }
+
+  #[test]
+  fn should_error_on_streaming_bulk_string_without_crlf() {
+    let mut buf: BytesMut = "$5\r\nfoobarbaz".into();
+
+    let mut continuation = match decode_streaming(&mut buf, 2).unwrap() {
+      Some(StreamingFrame::BulkStringStreaming { continuation, .. }) => continuation,
+      f => panic!("Expected streaming bulk string, found {:?}", f)
+    };
+    assert!(continuation.next_chunk(&mut buf).is_err());
+  }
+
+  #[test]
+  fn should_decode_normal_error() {
+    let mut bytes: BytesMut = "-WRONGTYPE Operation against a key holding the wrong kind of value\r\n".into();
+    let expected = (Some(Frame::Error("WRONGTYPE Operation against a key holding the wrong kind of value".into())), bytes.len());
+
+    decode_and_verify_some(&mut bytes, &expected);
+    decode_and_verify_padded_some(&mut bytes, &expected);
+  }
+
+  #[test]
+  fn should_decode_moved_error() {
+    let mut bytes: BytesMut = "-MOVED 3999 127.0.0.1:6381\r\n".into();
+    let expected = (Some(Frame::Moved("3999 127.0.0.1:6381".into())), bytes.len());
+
+    decode_and_verify_some(&mut bytes, &expected);
+    decode_and_verify_padded_some(&mut bytes, &expected);
+  }
+
+  #[test]
+  fn should_decode_moved_error_without_splitting_redirections() {
+    let options = DecodeOptions {
+      split_redirections: false,
+      ..Default::default()
+    };
+    let bytes = b"-MOVED 3999 127.0.0.1:6381\r\n";
+
+    let expected = (Some(Frame::Error("MOVED 3999 127.0.0.1:6381".into())), bytes.len());
+    assert_eq!(decode_with_options(bytes, &options).unwrap(), expected);
+
     let expected = (Some(Frame::Moved("3999 127.0.0.1:6381".into())), bytes.len());
+    assert_eq!(decode_with_options(bytes, &DecodeOptions::default()).unwrap(), expected);
+  }
+
+  #[test]
+  fn should_decode_ask_error_without_splitting_redirections() {
+    let options = DecodeOptions {
+      split_redirections: false,
+      ..Default::default()
+    };
+    let frame = decode_with_options(b"*1\r\n-ASK 3999 127.0.0.1:6381\r\n", &options).unwrap().0.unwrap();
 
-    decode_and_verify_some(&mut bytes, &expected);
-    decode_and_verify_padded_some(&mut bytes, &expected);
+    assert_eq!(frame, Frame::Array(vec![Frame::Error("ASK 3999 127.0.0.1:6381".into())]));
   }
 
   #[test]
@@ -291,6 +2248,652 @@ mod tests {
     decode_and_verify_none(&mut bytes);
   }
 
+  #[test]
+  fn should_decode_view_borrowing_simple_string() {
+    let buf = "+OK\r\n".as_bytes();
+
+    let (view, len) = decode_view(buf).unwrap();
+    let view = view.expect("Expected frame view");
+
+    assert_eq!(len, 5);
+    match view {
+      FrameView::SimpleString(s) => {
+        assert_eq!(s, b"OK");
+        assert_eq!(s.as_ptr(), buf[1..].as_ptr());
+      },
+      _ => panic!("Expected simple string view, found {:?}", view)
+    };
+    assert_eq!(view.to_owned().unwrap(), Frame::SimpleString("OK".into()));
+  }
+
+  #[test]
+  fn should_read_view_simple_string_without_allocating() {
+    let buf = "+OK\r\n".as_bytes();
+
+    let (view, _) = decode_view(buf).unwrap();
+    match view.expect("Expected frame view").as_str() {
+      Some(Cow::Borrowed(s)) => {
+        assert_eq!(s, "OK");
+        assert_eq!(s.as_ptr(), buf[1..].as_ptr());
+      },
+      s => panic!("Expected borrowed string, found {:?}", s)
+    };
+  }
+
+  #[test]
+  fn should_not_read_non_utf8_view_as_string() {
+    let view = FrameView::SimpleString(&[b'O', 0xff, b'K']);
+
+    assert!(view.as_str().is_none());
+    assert!(view.to_owned().is_err());
+    assert!(FrameView::BulkString(&[0xc3]).as_str().is_none());
+    assert!(FrameView::Integer(1).as_str().is_none());
+  }
+
+  #[test]
+  fn should_decode_view_array_to_owned() {
+    let bytes: BytesMut = "*3\r\n$3\r\nFoo\r\n$-1\r\n-MOVED 3999 127.0.0.1:6381\r\n".into();
+
+    let (view, len) = decode_view(&bytes).unwrap();
+    let view = view.expect("Expected frame view");
+    let expected = Frame::Array(vec![
+      Frame::BulkString(str_to_bytes("Foo")),
+      Frame::Null,
+      Frame::Moved("3999 127.0.0.1:6381".into())
+    ]);
+
+    assert_eq!(len, bytes.len());
+    assert_eq!(view.kind(), FrameKind::Array);
+    assert_eq!(view.to_owned().unwrap(), expected);
+  }
+
+  #[test]
+  fn should_decode_view_incomplete() {
+    let bytes: BytesMut = "*3\r\n$3\r\nFoo\r\n$-1\r\n$3\r\nBar".into();
+    let (view, len) = decode_view(&bytes).unwrap();
+
+    assert!(view.is_none());
+    assert_eq!(len, 0);
+  }
+
+  #[test]
+  fn should_slice_view_payload_from_bytes_without_copying() {
+    let payload = "0123456789012345678901234567890123456789";
+    let src = Bytes::from(format!("$40\r\n{}\r\n", payload));
+
+    let (view, len) = decode_view(&src).unwrap();
+    let view = view.expect("Expected frame view");
+    let sliced = view.payload_bytes(&src).expect("Expected payload bytes");
+
+    assert_eq!(len, decode(&src).unwrap().1);
+    assert_eq!(&sliced[..], payload.as_bytes());
+    assert_eq!(sliced.as_ptr(), src[5..].as_ptr());
+
+    drop(src);
+    assert_eq!(&sliced[..], payload.as_bytes());
+  }
+
+  #[test]
+  fn should_not_slice_view_payload_from_other_bytes() {
+    let src = Bytes::from("$3\r\nFoo\r\n");
+    let other = Bytes::from("$3\r\nFoo\r\n");
+
+    let (view, _) = decode_view(&src).unwrap();
+    let view = view.expect("Expected frame view");
+
+    assert!(view.payload_bytes(&other).is_none());
+    assert!(FrameView::Integer(1).payload_bytes(&src).is_none());
+  }
+
+  #[test]
+  fn should_error_converting_non_utf8_view_to_owned() {
+    let view = FrameView::SimpleString(&[0xff, 0xfe]);
+    let e = view.to_owned().unwrap_err();
+
+    assert_eq!(e.kind(), &RedisProtocolErrorKind::DecodeError);
+  }
+
+  fn decode_and_verify_invalid_length(bytes: &BytesMut) {
+    match decode_bytes(bytes) {
+      Ok(r) => panic!("Expected invalid length error, found {:?}", r),
+      Err(e) => {
+        assert_eq!(e.kind(), &RedisProtocolErrorKind::DecodeError);
+        assert_eq!(e.to_string(), "Decode Error: Invalid length prefix.");
+      }
+    }
+  }
+
+  #[test]
+  fn should_error_on_bulk_string_len_leading_space() {
+    let bytes: BytesMut = "$ 3\r\nfoo\r\n".into();
+    decode_and_verify_invalid_length(&bytes);
+  }
+
+  #[test]
+  fn should_error_on_bulk_string_len_trailing_space() {
+    let bytes: BytesMut = "$3 \r\nfoo\r\n".into();
+    decode_and_verify_invalid_length(&bytes);
+  }
+
+  #[test]
+  fn should_error_on_bulk_string_len_out_of_range() {
+    let e = decode(b"$99999999999999999999\r\nfoo\r\n").unwrap_err();
+
+    assert_eq!(e.kind(), &RedisProtocolErrorKind::DecodeError);
+    assert_eq!(e.to_string(), "Decode Error: Length prefix out of range: 99999999999999999999.");
+    assert_eq!(decode(b"*99999999999999999999\r\n").unwrap_err().to_string(), "Decode Error: Length prefix out of range: 99999999999999999999.");
+  }
+
+  #[test]
+  fn should_decode_large_bulk_string_len_in_range() {
+    let mut bytes = BytesMut::from("$1024\r\n");
+    assert_eq!(decode_bytes(&bytes).unwrap(), (None, 0));
+    assert_eq!(bulkstring_remaining(&bytes), Some(1026));
+
+    bytes.extend_from_slice(&[b'a'; 1024]);
+    bytes.extend_from_slice(b"\r\n");
+    assert_eq!(decode_bytes(&bytes).unwrap(), (Some(Frame::BulkString(vec![b'a'; 1024])), 1033));
+  }
+
+  #[test]
+  fn should_error_on_bulk_string_len_plus_sign() {
+    let bytes: BytesMut = "$+3\r\nfoo\r\n".into();
+    decode_and_verify_invalid_length(&bytes);
+  }
+
+  #[test]
+  fn should_error_on_array_len_plus_sign() {
+    let bytes: BytesMut = "*+1\r\n$3\r\nfoo\r\n".into();
+    decode_and_verify_invalid_length(&bytes);
+  }
+
+  fn decode_and_verify_invalid_integer(bytes: &BytesMut) {
+    match decode_bytes(bytes) {
+      Ok(r) => panic!("Expected invalid integer error, found {:?}", r),
+      Err(e) => {
+        assert_eq!(e.kind(), &RedisProtocolErrorKind::DecodeError);
+        assert_eq!(e.to_string(), "Decode Error: Invalid integer.");
+      }
+    }
+  }
+
+  #[test]
+  fn should_error_on_integer_plus_sign_only() {
+    let bytes: BytesMut = ":+\r\n".into();
+    decode_and_verify_invalid_integer(&bytes);
+  }
+
+  #[test]
+  fn should_error_on_integer_minus_sign_only() {
+    let bytes: BytesMut = ":-\r\n".into();
+    decode_and_verify_invalid_integer(&bytes);
+  }
+
+  #[test]
+  fn should_error_on_empty_integer() {
+    let bytes: BytesMut = ":\r\n".into();
+    decode_and_verify_invalid_integer(&bytes);
+  }
+
+  #[test]
+  fn should_error_on_view_of_minus_sign_only() {
+    let e = decode_view(b":-\r\n").unwrap_err();
+    assert_eq!(e.to_string(), "Decode Error: Invalid integer.");
+  }
+
+  #[test]
+  fn should_decode_negative_and_zero_integers() {
+    let mut bytes: BytesMut = ":-1\r\n".into();
+    decode_and_verify_some(&mut bytes, &(Some(Frame::Integer(-1)), 5));
+
+    let mut bytes: BytesMut = ":0\r\n".into();
+    decode_and_verify_some(&mut bytes, &(Some(Frame::Integer(0)), 4));
+  }
+
+  #[test]
+  fn should_decode_incomplete_integer() {
+    let mut bytes: BytesMut = ":-".into();
+    decode_and_verify_none(&mut bytes);
+  }
+
+  #[test]
+  fn should_decode_array_with_split_header_line() {
+    let mut bytes = BytesMut::new();
+
+    for chunk in ["*", "1", "\r", "\n", ":7\r"].iter() {
+      bytes.extend_from_slice(chunk.as_bytes());
+      assert_eq!(decode_bytes(&bytes).unwrap(), (None, 0));
+      assert_eq!(decode_iterative(&bytes).unwrap(), (None, 0));
+    }
+
+    bytes.extend_from_slice(b"\n+OK");
+    let expected = (Some(Frame::Array(vec![Frame::Integer(7)])), 8);
+    assert_eq!(decode_bytes(&bytes).unwrap(), expected);
+    assert_eq!(decode_iterative(&bytes).unwrap(), expected);
+  }
+
+  #[test]
+  fn should_decode_bulk_string_with_split_trailing_crlf() {
+    let mut bytes: BytesMut = "$3\r\nfoo\r".into();
+    assert_eq!(decode_bytes(&bytes).unwrap(), (None, 0));
+    assert_eq!(decode_iterative(&bytes).unwrap(), (None, 0));
+    assert_eq!(decode_view(&bytes).unwrap(), (None, 0));
+
+    bytes.extend_from_slice(b"\n");
+    let expected = (Some(Frame::BulkString(str_to_bytes("foo"))), 9);
+    assert_eq!(decode_bytes(&bytes).unwrap(), expected);
+    assert_eq!(decode_iterative(&bytes).unwrap(), expected);
+    assert_eq!(decode_view(&bytes).unwrap(), (Some(FrameView::BulkString(b"foo")), 9));
+  }
+
+  #[test]
+  fn should_decode_array_missing_trailing_frames() {
+    let mut bytes: BytesMut = "*3\r\n:1\r\n:2\r\n".into();
+    assert_eq!(decode_bytes(&bytes).unwrap(), (None, 0));
+    assert_eq!(decode_iterative(&bytes).unwrap(), (None, 0));
+
+    bytes.extend_from_slice(b":3\r\n");
+    let expected = (Some(Frame::Array(vec![Frame::Integer(1), Frame::Integer(2), Frame::Integer(3)])), 16);
+    assert_eq!(decode_bytes(&bytes).unwrap(), expected);
+    assert_eq!(decode_iterative(&bytes).unwrap(), expected);
+  }
+
+  #[test]
+  fn should_error_on_malformed_inner_frame_before_array_completes() {
+    let bytes = b"*3\r\n:1\r\n:x\r\n";
+
+    let e = decode(bytes).unwrap_err();
+    assert_eq!(e.kind(), &RedisProtocolErrorKind::DecodeError);
+    assert_eq!(decode_iterative(bytes).unwrap_err().kind(), &RedisProtocolErrorKind::DecodeError);
+    assert!(decode(b"*3\r\n:1\r\n&\r\n").is_err());
+  }
+
+  #[test]
+  fn should_decode_bulk_string_clean_len() {
+    let expected = (Some(Frame::BulkString(str_to_bytes("foo"))), 9);
+    let mut bytes: BytesMut = "$3\r\nfoo\r\n".into();
+
+    decode_and_verify_some(&mut bytes, &expected);
+  }
+
+  #[test]
+  fn should_split_pipelined_frames() {
+    let mut bytes: BytesMut = ":1\r\n$3\r\nfoo\r\n*2\r\n+OK\r\n$-1\r\n*2\r\n$3\r\nbar".into();
+
+    let frames = split_frames(&mut bytes).unwrap();
+    let expected: Vec<Bytes> = vec![
+      ":1\r\n".into(),
+      "$3\r\nfoo\r\n".into(),
+      "*2\r\n+OK\r\n$-1\r\n".into()
+    ];
+
+    assert_eq!(frames, expected);
+    assert_eq!(bytes, "*2\r\n$3\r\nbar".as_bytes());
+  }
+
+  #[test]
+  fn should_read_next_frame_size_from_bulk_header() {
+    assert_eq!(next_frame_size(b"$100\r\n").unwrap(), FrameSize::Known(100 + 6 + 2));
+    assert_eq!(next_frame_size(b"$100\r\nfoo").unwrap(), FrameSize::Known(108));
+    assert_eq!(next_frame_size(b"$3\r\nfoo\r\n:1\r\n").unwrap(), FrameSize::Known(9));
+    assert_eq!(next_frame_size(b"$-1\r\n").unwrap(), FrameSize::Known(5));
+  }
+
+  #[test]
+  fn should_read_unknown_next_frame_size() {
+    assert_eq!(next_frame_size(b"").unwrap(), FrameSize::Unknown);
+    assert_eq!(next_frame_size(b"$10").unwrap(), FrameSize::Unknown);
+    assert_eq!(next_frame_size(b"$10\r").unwrap(), FrameSize::Unknown);
+    assert_eq!(next_frame_size(b"*2\r\n$3\r\nfoo\r\n").unwrap(), FrameSize::Unknown);
+    assert_eq!(next_frame_size(b"+OK").unwrap(), FrameSize::Unknown);
+  }
+
+  #[test]
+  fn should_read_next_frame_size_of_simple_string() {
+    assert_eq!(next_frame_size(b"+OK\r\n").unwrap(), FrameSize::Known(5));
+    assert_eq!(next_frame_size(b"*1\r\n+OK\r\n").unwrap(), FrameSize::Known(9));
+    assert!(next_frame_size(b"$-5\r\n").is_err());
+  }
+
+  #[test]
+  fn should_decode_raw_frame() {
+    let mut bytes: BytesMut = ":007\r\n-MOVED 3999 127.0.0.1:6381\r\n$3\r\nfo".into();
+    let mut out = BytesMut::new();
+
+    let raw = decode_raw(&mut bytes).unwrap().unwrap();
+    assert_eq!(raw.frame, Frame::Integer(7));
+    raw.forward(&mut out);
+
+    let raw = decode_raw(&mut bytes).unwrap().unwrap();
+    assert!(raw.frame.is_moved_or_ask_error());
+    raw.forward(&mut out);
+
+    assert_eq!(&out[..], &b":007\r\n-MOVED 3999 127.0.0.1:6381\r\n"[..]);
+    assert_eq!(decode_raw(&mut bytes).unwrap(), None);
+    assert_eq!(&bytes[..], &b"$3\r\nfo"[..]);
+  }
+
+  #[test]
+  fn should_decode_raw_frame_owned() {
+    let mut bytes: BytesMut = "$40\r\n0123456789012345678901234567890123456789\r\n".into();
+    let source = bytes.as_ptr();
+
+    let raw = decode_raw_with_mode(&mut bytes, CopyMode::Owned).unwrap().unwrap();
+    let payload = raw.payload.expect("Expected payload");
+
+    assert_ne!(raw.bytes.as_ptr(), source);
+    assert_eq!(&raw.bytes[..], &b"$40\r\n0123456789012345678901234567890123456789\r\n"[..]);
+    assert_eq!(raw.frame, Frame::BulkString(str_to_bytes("0123456789012345678901234567890123456789")));
+    assert!(bytes.is_empty());
+
+    assert_eq!(&payload[..], &b"0123456789012345678901234567890123456789"[..]);
+    assert_ne!(payload.as_ptr() as usize, source as usize + 5);
+    assert_eq!(payload.as_ptr(), raw.bytes[5..].as_ptr());
+  }
+
+  #[test]
+  fn should_decode_raw_frame_borrowed() {
+    let mut bytes: BytesMut = "$40\r\n0123456789012345678901234567890123456789\r\n+OK\r\n".into();
+    let source = bytes.as_ptr();
+
+    let raw = decode_raw_with_mode(&mut bytes, CopyMode::Borrowed).unwrap().unwrap();
+    let payload = raw.payload.expect("Expected payload");
+
+    assert_eq!(raw.bytes.as_ptr(), source);
+    assert_eq!(raw.bytes.len(), 47);
+    assert_eq!(&bytes[..], &b"+OK\r\n"[..]);
+
+    assert_eq!(&payload[..], &b"0123456789012345678901234567890123456789"[..]);
+    assert_eq!(payload.as_ptr() as usize, source as usize + 5);
+  }
+
+  #[test]
+  fn should_decode_raw_frame_without_payload() {
+    let mut bytes: BytesMut = ":1\r\n*1\r\n$3\r\nfoo\r\n".into();
+
+    assert_eq!(decode_raw(&mut bytes).unwrap().unwrap().payload, None);
+    assert_eq!(decode_raw(&mut bytes).unwrap().unwrap().payload, None);
+
+    let mut bytes: BytesMut = "$?\r\n;3\r\nfoo\r\n;0\r\n".into();
+    let raw = decode_raw(&mut bytes).unwrap().unwrap();
+    assert_eq!(raw.frame, Frame::BulkString(str_to_bytes("foo")));
+    assert_eq!(raw.payload, None);
+  }
+
+  #[test]
+  fn should_decode_raw_frame_payload_of_each_string_kind() {
+    let mut bytes: BytesMut = "+OK\r\n-MOVED 3999 127.0.0.1:6381\r\n(-123\r\n=7\r\ntxt:foo\r\n$0\r\n\r\n".into();
+    let expected: Vec<&[u8]> = vec![b"OK", b"MOVED 3999 127.0.0.1:6381", b"-123", b"foo", b""];
+
+    for payload in expected.into_iter() {
+      let raw = decode_raw(&mut bytes).unwrap().unwrap();
+      assert_eq!(raw.payload, Some(Bytes::from(payload)));
+    }
+    assert!(bytes.is_empty());
+  }
+
+  #[test]
+  fn should_error_decoding_raw_frame() {
+    let mut bytes: BytesMut = "=3\r\ntxt\r\n".into();
+
+    assert!(decode_raw(&mut bytes).is_err());
+    assert_eq!(&bytes[..], &b"=3\r\ntxt\r\n"[..]);
+  }
+
+  #[test]
+  fn should_decode_only_push_frames() {
+    let mut decoder = PushDecoder::new();
+    decoder.extend_from_slice(b"*3\r\n$9\r\nsubscribe\r\n$3\r\nfoo\r\n:1\r\n>3\r\n$7\r\nmessage\r\n$3\r\nfoo\r\n$3\r\nb");
+
+    let expected = Frame::Array(vec![
+      Frame::BulkString(str_to_bytes("subscribe")),
+      Frame::BulkString(str_to_bytes("foo")),
+      Frame::Integer(1)
+    ]);
+    assert_eq!(decoder.decode().unwrap(), Some(expected));
+    assert_eq!(decoder.decode().unwrap(), None);
+
+    decoder.extend_from_slice(b"ar\r\n*3\r\n$7\r\nmessage\r\n$3\r\nfoo\r\n$3\r\nbaz\r\n");
+    let expected = Frame::Push(vec![
+      Frame::BulkString(str_to_bytes("message")),
+      Frame::BulkString(str_to_bytes("foo")),
+      Frame::BulkString(str_to_bytes("bar"))
+    ]);
+    assert_eq!(decoder.decode().unwrap(), Some(expected));
+    assert!(decoder.decode().unwrap().unwrap().is_pubsub_message());
+    assert_eq!(decoder.decode().unwrap(), None);
+    assert!(decoder.buffer().is_empty());
+  }
+
+  #[test]
+  fn should_error_on_unexpected_frame_when_decoding_pushes() {
+    let mut decoder = PushDecoder::new();
+    decoder.extend_from_slice(b":1\r\n>2\r\n$4\r\npong\r\n$0\r\n\r\n");
+
+    let e = decoder.decode().unwrap_err();
+    assert_eq!(e.kind(), &RedisProtocolErrorKind::DecodeError);
+    assert_eq!(e.to_string(), "Decode Error: Unexpected Integer frame on publish-subscribe connection.");
+
+    assert_eq!(decoder.decode().unwrap().unwrap().kind(), FrameKind::Push);
+  }
+
+  #[test]
+  fn should_split_frames_empty() {
+    let mut bytes = empty_bytes();
+    let frames = split_frames(&mut bytes).unwrap();
+
+    assert!(frames.is_empty());
+  }
+
+  #[test]
+  fn should_error_on_line_longer_than_max_line_len() {
+    let options = DecodeOptions {
+      max_line_len: Some(64 * 1024),
+      ..Default::default()
+    };
+    let mut bytes = BytesMut::from("+".as_bytes());
+    bytes.extend_from_slice(&vec![b'a'; 1024 * 1024]);
+
+    let e = decode_bytes_with_options(&bytes, &options).unwrap_err();
+    assert_eq!(e.kind(), &RedisProtocolErrorKind::DecodeError);
+    assert_eq!(e.to_string(), "Decode Error: Line too long.");
+
+    bytes.extend_from_slice(CRLF.as_bytes());
+    let e = decode_bytes_with_options(&bytes, &options).unwrap_err();
+    assert_eq!(e.kind(), &RedisProtocolErrorKind::DecodeError);
+  }
+
+  #[test]
+  fn should_decode_line_within_max_line_len() {
+    let options = DecodeOptions {
+      max_line_len: Some(6),
+      ..Default::default()
+    };
+
+    let bytes: BytesMut = "+strings\r\n".into();
+    assert!(decode_bytes_with_options(&bytes, &options).is_err());
+
+    let bytes: BytesMut = "+string\r\n".into();
+    let (frame, len) = decode_bytes_with_options(&bytes, &options).unwrap();
+    assert_eq!(frame, Some(Frame::SimpleString("string".into())));
+    assert_eq!(len, 9);
+
+    let bytes: BytesMut = "*2\r\n:123456\r\n-ERR\r\n".into();
+    let (frame, len) = decode_bytes_with_options(&bytes, &options).unwrap();
+    assert_eq!(frame, Some(Frame::Array(vec![Frame::Integer(123456), Frame::Error("ERR".into())])));
+    assert_eq!(len, bytes.len());
+  }
+
+  #[test]
+  fn should_decode_incomplete_line_within_max_line_len() {
+    let options = DecodeOptions {
+      max_line_len: Some(64 * 1024),
+      ..Default::default()
+    };
+    let mut bytes = BytesMut::from("+".as_bytes());
+    bytes.extend_from_slice(&vec![b'a'; 1024]);
+
+    let (frame, len) = decode_bytes_with_options(&bytes, &options).unwrap();
+    assert!(frame.is_none());
+    assert_eq!(len, 0);
+  }
+
+  #[test]
+  fn should_decode_lf_terminated_error_when_lenient() {
+    let options = DecodeOptions {
+      lenient_newlines: true,
+      ..Default::default()
+    };
+
+    let bytes: BytesMut = "-ERR x\n".into();
+    let (frame, len) = decode_bytes_with_options(&bytes, &options).unwrap();
+    assert_eq!(frame, Some(Frame::Error("ERR x".into())));
+    assert_eq!(len, 7);
+
+    let bytes: BytesMut = "-ERR x\r\n".into();
+    let (frame, len) = decode_bytes_with_options(&bytes, &options).unwrap();
+    assert_eq!(frame, Some(Frame::Error("ERR x".into())));
+    assert_eq!(len, 8);
+
+    let bytes: BytesMut = "*2\r\n-ERR x\n:1\n".into();
+    let (frame, len) = decode_bytes_with_options(&bytes, &options).unwrap();
+    assert_eq!(frame, Some(Frame::Array(vec![Frame::Error("ERR x".into()), Frame::Integer(1)])));
+    assert_eq!(len, bytes.len());
+  }
+
+  #[test]
+  fn should_not_decode_lf_terminated_error_when_strict() {
+    let bytes: BytesMut = "-ERR x\n".into();
+    let (frame, len) = decode_bytes(&bytes).unwrap();
+    assert!(frame.is_none());
+    assert_eq!(len, 0);
+  }
+
+  #[test]
+  fn should_error_on_lenient_line_longer_than_max_line_len() {
+    let options = DecodeOptions {
+      lenient_newlines: true,
+      max_line_len: Some(6),
+      ..Default::default()
+    };
+
+    let bytes: BytesMut = "+strings\n".into();
+    assert!(decode_bytes_with_options(&bytes, &options).is_err());
+    let bytes: BytesMut = "+stringsandmore".into();
+    assert!(decode_bytes_with_options(&bytes, &options).is_err());
+
+    let bytes: BytesMut = "+string\n".into();
+    let (frame, len) = decode_bytes_with_options(&bytes, &options).unwrap();
+    assert_eq!(frame, Some(Frame::SimpleString("string".into())));
+    assert_eq!(len, 8);
+
+    let bytes: BytesMut = "+str".into();
+    let (frame, len) = decode_bytes_with_options(&bytes, &options).unwrap();
+    assert!(frame.is_none());
+    assert_eq!(len, 0);
+  }
+
+  #[test]
+  fn should_error_on_frame_larger_than_max_total_size() {
+    let options = DecodeOptions {
+      max_total_size: Some(1024),
+      ..Default::default()
+    };
+    let mut bytes = BytesMut::from("*100\r\n".as_bytes());
+    for _ in 0..100 {
+      bytes.extend_from_slice(b"$64\r\n");
+      bytes.extend_from_slice(&[b'a'; 64]);
+      bytes.extend_from_slice(CRLF.as_bytes());
+    }
+
+    let e = decode_bytes_with_options(&bytes, &options).unwrap_err();
+    assert_eq!(e.kind(), &RedisProtocolErrorKind::DecodeError);
+    assert_eq!(e.to_string(), "Decode Error: Max total size exceeded.");
+
+    // the limit is reached before the rest of the array is received
+    let e = decode_with_options(&bytes[0..2048], &options).unwrap_err();
+    assert_eq!(e.to_string(), "Decode Error: Max total size exceeded.");
+  }
+
+  #[test]
+  fn should_decode_frame_within_max_total_size() {
+    let options = DecodeOptions {
+      max_total_size: Some(23),
+      ..Default::default()
+    };
+
+    let bytes: BytesMut = "*2\r\n$3\r\nfoo\r\n$3\r\nbar\r\n:1\r\n".into();
+    let (frame, len) = decode_bytes_with_options(&bytes, &options).unwrap();
+    assert_eq!(frame, Some(Frame::Array(vec![Frame::BulkString(str_to_bytes("foo")), Frame::BulkString(str_to_bytes("bar"))])));
+    assert_eq!(len, 22);
+
+    let bytes: BytesMut = "*2\r\n$3\r\nfoo\r\n$3\r\nba".into();
+    let (frame, len) = decode_bytes_with_options(&bytes, &options).unwrap();
+    assert!(frame.is_none());
+    assert_eq!(len, 0);
+  }
+
+  #[test]
+  fn should_decode_with_stats() {
+    let mut bytes: BytesMut = ":1\r\n+OK\r\n:2\r\n*2\r\n$3\r\nfoo\r\n:3\r\n$-1\r\n-ERR\r\n$3\r\nba".into();
+    let total = bytes.len();
+    let mut stats = DecodeStats::default();
+
+    loop {
+      let len = match decode_with_stats(&bytes, &mut stats) {
+        Ok((Some(_), len)) => len,
+        Ok((None, _))      => break,
+        Err(e)             => return pretty_print_panic(e)
+      };
+
+      let _ = bytes.split_to(len);
+    }
+
+    assert_eq!(stats.frames(&FrameKind::Integer), 2);
+    assert_eq!(stats.frames(&FrameKind::SimpleString), 1);
+    assert_eq!(stats.frames(&FrameKind::Array), 1);
+    assert_eq!(stats.frames(&FrameKind::Null), 1);
+    assert_eq!(stats.frames(&FrameKind::Error), 1);
+    assert_eq!(stats.frames(&FrameKind::BulkString), 0);
+    assert_eq!(stats.incomplete, 1);
+    assert_eq!(stats.bytes_consumed, total - bytes.len());
+
+    stats.reset();
+    assert_eq!(stats, DecodeStats::default());
+  }
+
+  #[test]
+  fn should_decode_length_prefixed_round_trip() {
+    let frame = Frame::Array(vec![
+      Frame::BulkString(str_to_bytes("LLEN")),
+      Frame::BulkString(str_to_bytes("mylist"))
+    ]);
+    let mut bytes = empty_bytes();
+    let _ = ::encode::encode_length_prefixed(&mut bytes, &frame).unwrap();
+    bytes.extend_from_slice(PADDING.as_bytes());
+
+    let (decoded, len) = decode_length_prefixed(&bytes).unwrap();
+    assert_eq!(decoded, Some(frame));
+    assert_eq!(len, bytes.len() - PADDING.len());
+  }
+
+  #[test]
+  fn should_decode_length_prefixed_incomplete() {
+    let bytes: BytesMut = "\x00\x00\x00\x09$3\r\nfo".into();
+    assert_eq!(decode_length_prefixed(&bytes).unwrap(), (None, 0));
+
+    let bytes: BytesMut = "\x00\x00".into();
+    assert_eq!(decode_length_prefixed(&bytes).unwrap(), (None, 0));
+  }
+
+  #[test]
+  fn should_error_on_length_prefix_mismatch() {
+    let bytes: BytesMut = "\x00\x00\x00\x05$3\r\nfoo\r\n".into();
+    let e = decode_length_prefixed(&bytes).unwrap_err();
+
+    assert_eq!(e.kind(), &RedisProtocolErrorKind::DecodeError);
+  }
+
   #[test]
   #[should_panic]
   fn should_error_on_junk() {
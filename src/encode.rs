@@ -4,11 +4,62 @@ use ::types::*;
 
 use utils::{
   CRLF,
-  NULL
+  NULL,
+  RESP3_NULL
+};
+
+use std::io::{
+  Error as IoError,
+  ErrorKind as IoErrorKind,
+  Write
+};
+
+use std::sync::{
+  Mutex,
+  MutexGuard
 };
 
 use cookie_factory::GenError;
-use bytes::BytesMut;
+use bytes::{
+  BufMut,
+  Bytes,
+  BytesMut
+};
+
+// precomputed headers for arrays with up to 16 elements, which covers most commands
+const SMALL_ARRAY_HEADERS: [&'static str; 17] = [
+  "*0\r\n", "*1\r\n", "*2\r\n", "*3\r\n", "*4\r\n", "*5\r\n", "*6\r\n", "*7\r\n", "*8\r\n",
+  "*9\r\n", "*10\r\n", "*11\r\n", "*12\r\n", "*13\r\n", "*14\r\n", "*15\r\n", "*16\r\n"
+];
+
+fn small_array_header(len: usize) -> Option<&'static [u8]> {
+  SMALL_ARRAY_HEADERS.get(len).map(|header| header.as_bytes())
+}
+
+// the longest decimal is i64::MIN, which has a sign and 19 digits
+const MAX_DECIMAL_LEN: usize = 20;
+
+// format `val` into the end of the stack buffer without allocating, returning the written bytes
+fn write_decimal(buf: &mut [u8; MAX_DECIMAL_LEN], val: i64) -> &[u8] {
+  let mut n = val.unsigned_abs();
+  let mut pos = buf.len();
+
+  loop {
+    pos -= 1;
+    buf[pos] = b'0' + (n % 10) as u8;
+    n /= 10;
+
+    if n == 0 {
+      break;
+    }
+  }
+  if val < 0 {
+    pos -= 1;
+    buf[pos] = b'-';
+  }
+
+  &buf[pos..]
+}
 
 fn gen_simplestring<'a>(x: (&'a mut [u8], usize), data: &str) -> Result<(&'a mut [u8], usize), GenError> {
   let _ = utils::check_offset(&x);
@@ -54,9 +105,88 @@ fn gen_integer<'a>(x: (&'a mut [u8], usize), data: &i64) -> Result<(&'a mut [u8]
     return Err(GenError::BufferTooSmall(required - remaining));
   }
 
+  if let Some(encoded) = utils::small_integer_encoding(*data) {
+    return do_gen!(x, gen_slice!(encoded.as_bytes()));
+  }
+
+  let mut digits = [0; MAX_DECIMAL_LEN];
+  let decimal = write_decimal(&mut digits, *data);
+
   do_gen!(x,
     gen_be_u8!(FrameKind::Integer.to_byte()) >>
-    gen_slice!(data.to_string().as_bytes()) >>
+    gen_slice!(decimal) >>
+    gen_slice!(CRLF.as_bytes())
+  )
+}
+
+fn gen_double<'a>(x: (&'a mut [u8], usize), data: &f64) -> Result<(&'a mut [u8], usize), GenError> {
+  let _ = utils::check_offset(&x);
+
+  let required = utils::double_encode_len(*data);
+  let remaining = x.0.len() - x.1;
+
+  if remaining < required {
+    return Err(GenError::BufferTooSmall(required - remaining));
+  }
+
+  do_gen!(x,
+    gen_be_u8!(FrameKind::Double.to_byte()) >>
+    gen_slice!(utils::double_to_string(*data).as_bytes()) >>
+    gen_slice!(CRLF.as_bytes())
+  )
+}
+
+fn gen_boolean<'a>(x: (&'a mut [u8], usize), data: &bool) -> Result<(&'a mut [u8], usize), GenError> {
+  let _ = utils::check_offset(&x);
+
+  let required = utils::boolean_encode_len();
+  let remaining = x.0.len() - x.1;
+
+  if remaining < required {
+    return Err(GenError::BufferTooSmall(required - remaining));
+  }
+
+  do_gen!(x,
+    gen_be_u8!(FrameKind::Boolean.to_byte()) >>
+    gen_be_u8!(if *data { b't' }else{ b'f' }) >>
+    gen_slice!(CRLF.as_bytes())
+  )
+}
+
+fn gen_bignumber<'a>(x: (&'a mut [u8], usize), data: &str) -> Result<(&'a mut [u8], usize), GenError> {
+  let _ = utils::check_offset(&x);
+
+  let required = utils::bignumber_encode_len(data);
+  let remaining = x.0.len() - x.1;
+
+  if remaining < required {
+    return Err(GenError::BufferTooSmall(required - remaining));
+  }
+
+  do_gen!(x,
+    gen_be_u8!(FrameKind::BigNumber.to_byte()) >>
+    gen_slice!(data.as_bytes()) >>
+    gen_slice!(CRLF.as_bytes())
+  )
+}
+
+fn gen_verbatimstring<'a>(x: (&'a mut [u8], usize), format: &VerbatimFormat, data: &[u8]) -> Result<(&'a mut [u8], usize), GenError> {
+  let _ = utils::check_offset(&x)?;
+
+  let required = utils::verbatimstring_encode_len(data);
+  let remaining = x.0.len() - x.1;
+
+  if remaining < required {
+    return Err(GenError::BufferTooSmall(required - remaining));
+  }
+
+  do_gen!(x,
+    gen_be_u8!(FrameKind::VerbatimString.to_byte()) >>
+    gen_slice!((utils::VERBATIM_FORMAT_LEN + data.len()).to_string().as_bytes()) >>
+    gen_slice!(CRLF.as_bytes()) >>
+    gen_slice!(&format.to_bytes()) >>
+    gen_be_u8!(b':') >>
+    gen_slice!(data) >>
     gen_slice!(CRLF.as_bytes())
   )
 }
@@ -93,10 +223,51 @@ fn gen_null(x: (&mut [u8], usize)) -> Result<(&mut [u8], usize), GenError> {
   do_gen!(x, gen_slice!(NULL.as_bytes()))
 }
 
+fn gen_null_resp3(x: (&mut [u8], usize)) -> Result<(&mut [u8], usize), GenError> {
+  let _ = utils::check_offset(&x)?;
+
+  let required = RESP3_NULL.as_bytes().len();
+  let remaining = x.0.len() - x.1;
+
+  if remaining < required {
+    return Err(GenError::BufferTooSmall(required - remaining));
+  }
+
+  do_gen!(x, gen_slice!(RESP3_NULL.as_bytes()))
+}
+
 fn gen_array<'a>(x: (&'a mut [u8], usize), data: &Vec<Frame>) -> Result<(&'a mut [u8], usize), GenError> {
   let _ = utils::check_offset(&x)?;
 
-  let required = utils::array_encode_len(data)?;
+  let required = utils::array_encode_len(data);
+  let remaining = x.0.len() - x.1;
+
+  if remaining < required {
+    return Err(GenError::BufferTooSmall(required - remaining));
+  }
+
+  let mut x = match small_array_header(data.len()) {
+    Some(header) => do_gen!(x, gen_slice!(header))?,
+    None => do_gen!(x,
+      gen_be_u8!(FrameKind::Array.to_byte()) >>
+      gen_slice!(data.len().to_string().as_bytes()) >>
+      gen_slice!(CRLF.as_bytes())
+    )?
+  };
+
+  for frame in data.iter() {
+    x = gen_frame(x, frame)?;
+  }
+
+  // no trailing CRLF here, the inner values add that
+  Ok(x)
+}
+
+// used by sets and pushes, which share the array format
+fn gen_aggregate<'a>(x: (&'a mut [u8], usize), kind: FrameKind, data: &Vec<Frame>) -> Result<(&'a mut [u8], usize), GenError> {
+  let _ = utils::check_offset(&x)?;
+
+  let required = utils::array_encode_len(data);
   let remaining = x.0.len() - x.1;
 
   if remaining < required {
@@ -104,37 +275,69 @@ fn gen_array<'a>(x: (&'a mut [u8], usize), data: &Vec<Frame>) -> Result<(&'a mut
   }
 
   let mut x = do_gen!(x,
-    gen_be_u8!(FrameKind::Array.to_byte()) >>
+    gen_be_u8!(kind.to_byte()) >>
     gen_slice!(data.len().to_string().as_bytes()) >>
     gen_slice!(CRLF.as_bytes())
   )?;
 
   for frame in data.iter() {
-    x = match frame {
-      Frame::BulkString(ref b) => gen_bulkstring(x, &b)?,
-      Frame::Null              => gen_null(x)?,
-      Frame::Array(ref frames) => gen_array(x, frames)?,
-      _ => return Err(GenError::CustomError(1))
-    };
+    x = gen_frame(x, frame)?;
   }
 
-  // no trailing CRLF here, the inner values add that
   Ok(x)
 }
 
-fn attempt_encoding(buf: &mut [u8], offset: usize, frame: &Frame) -> Result<usize, GenError> {
+// used by maps and attributes, where the length prefix is the number of key/value pairs
+fn gen_map<'a>(x: (&'a mut [u8], usize), kind: FrameKind, data: &[(Frame, Frame)]) -> Result<(&'a mut [u8], usize), GenError> {
+  let _ = utils::check_offset(&x)?;
+
+  let required = utils::map_encode_len(data);
+  let remaining = x.0.len() - x.1;
+
+  if remaining < required {
+    return Err(GenError::BufferTooSmall(required - remaining));
+  }
+
+  let mut x = do_gen!(x,
+    gen_be_u8!(kind.to_byte()) >>
+    gen_slice!(data.len().to_string().as_bytes()) >>
+    gen_slice!(CRLF.as_bytes())
+  )?;
+
+  for (key, value) in data.iter() {
+    x = gen_frame(x, key)?;
+    x = gen_frame(x, value)?;
+  }
+
+  Ok(x)
+}
+
+fn gen_frame<'a>(x: (&'a mut [u8], usize), frame: &Frame) -> Result<(&'a mut [u8], usize), GenError> {
   match *frame {
-    Frame::BulkString(ref b)   => gen_bulkstring((buf, offset), b).map(|(_, l)| l),
-    Frame::Null                => gen_null((buf, offset)).map(|(_, l)| l),
-    Frame::Array(ref frames)   => gen_array((buf, offset), frames).map(|(_, l)| l),
-    Frame::Error(ref s)        => gen_error((buf, offset), s).map(|(_, l)| l),
-    Frame::Moved(ref s)        => gen_error((buf, offset), s).map(|(_, l)| l),
-    Frame::Ask(ref s)          => gen_error((buf, offset), s).map(|(_, l)| l),
-    Frame::SimpleString(ref s) => gen_simplestring((buf, offset), s).map(|(_, l)| l),
-    Frame::Integer(ref i)      => gen_integer((buf, offset), i).map(|(_, l)| l)
+    Frame::BulkString(ref b)   => gen_bulkstring(x, b),
+    Frame::Null                => gen_null(x),
+    Frame::NullResp3           => gen_null_resp3(x),
+    Frame::Array(ref frames)   => gen_array(x, frames),
+    Frame::Error(ref s)        => gen_error(x, s),
+    Frame::Moved(ref s)        => gen_error(x, s),
+    Frame::Ask(ref s)          => gen_error(x, s),
+    Frame::SimpleString(ref s) => gen_simplestring(x, s),
+    Frame::Integer(ref i)      => gen_integer(x, i),
+    Frame::Double(ref d)       => gen_double(x, d),
+    Frame::Boolean(ref b)      => gen_boolean(x, b),
+    Frame::BigNumber(ref s)    => gen_bignumber(x, s),
+    Frame::VerbatimString { ref format, ref data } => gen_verbatimstring(x, format, data),
+    Frame::Set(ref frames)     => gen_aggregate(x, FrameKind::Set, frames),
+    Frame::Push(ref frames)    => gen_aggregate(x, FrameKind::Push, frames),
+    Frame::Map(ref pairs)      => gen_map(x, FrameKind::Map, pairs),
+    Frame::Attribute(ref p)    => gen_map(x, FrameKind::Attribute, p)
   }
 }
 
+fn attempt_encoding(buf: &mut [u8], offset: usize, frame: &Frame) -> Result<usize, GenError> {
+  gen_frame((buf, offset), frame).map(|(_, l)| l)
+}
+
 /// Attempt to encode a frame into `buf`, assuming a starting offset of 0.
 ///
 /// The caller is responsible for extending the buffer if a `RedisProtocolErrorKind::BufferTooSmall` is returned.
@@ -159,12 +362,251 @@ pub fn encode_bytes<'a>(buf: &'a mut BytesMut, frame: &Frame) -> Result<usize, R
   }
 }
 
+/// Attempt to encode each of `frames` into `buf` in order, extending the buffer as needed.
+///
+/// Returns the new length of the buffer.
+pub fn encode_many<'a>(buf: &'a mut BytesMut, frames: &[Frame]) -> Result<usize, RedisProtocolError<'a>> {
+  for frame in frames.iter() {
+    let _ = encode_bytes(buf, frame).map_err(|e| e.into_owned())?;
+  }
+
+  Ok(buf.len())
+}
+
+/// Attempt to encode a frame into a new buffer sized to fit the encoded frame.
+pub fn encode_to_bytes<'a>(frame: &Frame) -> Result<Bytes, RedisProtocolError<'a>> {
+  let len = utils::encode_len(frame);
+  let mut buf = BytesMut::with_capacity(len);
+  utils::zero_extend(&mut buf, len);
+
+  let written = attempt_encoding(&mut buf, 0, frame)?;
+  buf.truncate(written);
+  Ok(buf.freeze())
+}
+
+/// Encode a frame into a buffer sized to fit the encoded frame and write it to `writer` with a single `write_all` call,
+/// returning the number of bytes written.
+///
+/// Encoding errors are returned as `InvalidInput` IO errors. The writer is not flushed.
+pub fn write_frame_to<W: Write>(writer: &mut W, frame: &Frame) -> Result<usize, IoError> {
+  let buf = encode_to_bytes(frame).map_err(|e| IoError::new(IoErrorKind::InvalidInput, e.to_string()))?;
+
+  writer.write_all(&buf)?;
+  Ok(buf.len())
+}
+
+/// Attempt to encode a frame into `buf` after a 4 byte big endian header containing the encoded length of the frame, extending the buffer as needed.
+///
+/// Returns the new length of the buffer.
+pub fn encode_length_prefixed<'a>(buf: &'a mut BytesMut, frame: &Frame) -> Result<usize, RedisProtocolError<'a>> {
+  let len = utils::encode_len(frame);
+  if len > u32::MAX as usize {
+    return Err(RedisProtocolError::new(RedisProtocolErrorKind::EncodeError, "Frame too large for length prefix."));
+  }
+
+  buf.reserve(utils::LENGTH_PREFIX_LEN + len);
+  buf.put_u32_be(len as u32);
+
+  encode_bytes(buf, frame)
+}
+
+/// Attempt to encode a command array into `buf`, extending the buffer as needed, and read the cluster key slot of the keys
+/// in the command according to `key_spec`.
+///
+/// Returns the number of bytes written and the slot, or `None` if the command has no keys. If the keys map to different
+/// slots an error is returned without writing anything. See `Frame::same_slot` for more information.
+pub fn encode_routed(buf: &mut BytesMut, frame: &Frame, key_spec: KeySpec) -> Result<(usize, Option<u16>), RedisProtocolError<'static>> {
+  let slot = frame.same_slot(key_spec).map_err(|e| e.into_owned())?;
+  let offset = buf.len();
+  let len = encode_bytes(buf, frame).map_err(|e| e.into_owned())?;
+
+  Ok((len - offset, slot))
+}
+
+/// Write an array header for `count` elements to `buf`, followed by each of the already encoded `raw_elements` without
+/// re-encoding them.
+///
+/// Returns an `EncodeError` without writing anything if `count` is not the number of raw elements. The raw elements are not
+/// validated. Returns the new length of the buffer.
+pub fn encode_array_of_raw(buf: &mut BytesMut, count: usize, raw_elements: &[Bytes]) -> Result<usize, RedisProtocolError<'static>> {
+  if count != raw_elements.len() {
+    return Err(RedisProtocolError::new(
+      RedisProtocolErrorKind::EncodeError,
+      format!("Expected {} raw array elements, found {}.", count, raw_elements.len())
+    ));
+  }
+
+  let header_len = 1 + utils::digits_in_number(count) + CRLF.len();
+  buf.reserve(header_len + raw_elements.iter().map(|raw| raw.len()).sum::<usize>());
+
+  match small_array_header(count) {
+    Some(header) => buf.put_slice(header),
+    None => {
+      buf.put_u8(ARRAY_BYTE);
+      buf.put_slice(count.to_string().as_bytes());
+      buf.put_slice(CRLF.as_bytes());
+    }
+  };
+  for raw in raw_elements.iter() {
+    buf.put_slice(raw);
+  }
+
+  Ok(buf.len())
+}
+
+/// Write a RESP integer such as `:123\r\n` to `buf`, extending the buffer as needed, without going through a `Frame`.
+pub fn write_integer(buf: &mut BytesMut, val: i64) {
+  if let Some(encoded) = utils::small_integer_encoding(val) {
+    buf.extend_from_slice(encoded.as_bytes());
+    return;
+  }
+
+  let mut digits = [0; MAX_DECIMAL_LEN];
+  buf.reserve(utils::integer_encode_len(&val));
+
+  buf.put_u8(FrameKind::Integer.to_byte());
+  buf.put_slice(write_decimal(&mut digits, val));
+  buf.put_slice(CRLF.as_bytes());
+}
+
+/// Write the header of a RESP3 streamed string to `buf`.
+///
+/// The header should be followed by any number of chunks written with `write_bulk_chunk`, and then the terminator written with `write_stream_end`.
+pub fn write_stream_start(buf: &mut BytesMut) {
+  buf.extend_from_slice(utils::STREAMED_STRING_START.as_bytes());
+}
+
+/// Write a RESP3 streamed string chunk containing `data` to `buf`.
+///
+/// Nothing is written if `data` is empty, since a zero length chunk terminates the stream.
+pub fn write_bulk_chunk(buf: &mut BytesMut, data: &[u8]) {
+  if data.is_empty() {
+    return;
+  }
+
+  let len = data.len().to_string();
+  buf.reserve(1 + len.len() + CRLF.len() + data.len() + CRLF.len());
+
+  buf.put_u8(CHUNK_BYTE);
+  buf.put_slice(len.as_bytes());
+  buf.put_slice(CRLF.as_bytes());
+  buf.put_slice(data);
+  buf.put_slice(CRLF.as_bytes());
+}
+
+/// Write the terminator of a RESP3 streamed string to `buf`.
+pub fn write_stream_end(buf: &mut BytesMut) {
+  buf.extend_from_slice(utils::STREAM_END.as_bytes());
+}
+
+/// The default capacity of the buffers created by an `EncodeBufferPool`.
+pub const DEFAULT_POOL_BUFFER_CAPACITY: usize = 16 * utils::KB;
+
+/// A pool of reusable buffers for encoding frames, used with `encode_pooled`.
+///
+/// At most `max_buffers` idle buffers are kept, and buffers returned to a full pool are dropped.
+#[derive(Debug)]
+pub struct EncodeBufferPool {
+  buffers: Mutex<Vec<BytesMut>>,
+  max_buffers: usize,
+  buffer_capacity: usize
+}
+
+impl EncodeBufferPool {
+
+  /// Create a pool that keeps at most `max_buffers` idle buffers of `DEFAULT_POOL_BUFFER_CAPACITY` bytes.
+  pub fn new(max_buffers: usize) -> EncodeBufferPool {
+    EncodeBufferPool::with_buffer_capacity(max_buffers, DEFAULT_POOL_BUFFER_CAPACITY)
+  }
+
+  /// Create a pool that keeps at most `max_buffers` idle buffers, creating new buffers with `buffer_capacity` bytes.
+  pub fn with_buffer_capacity(max_buffers: usize, buffer_capacity: usize) -> EncodeBufferPool {
+    EncodeBufferPool {
+      buffers: Mutex::new(Vec::with_capacity(max_buffers)),
+      max_buffers,
+      buffer_capacity
+    }
+  }
+
+  /// Take an empty buffer from the pool, or create a new buffer if the pool is empty.
+  pub fn checkout(&self) -> BytesMut {
+    let buf = self.lock().pop();
+
+    match buf {
+      Some(mut buf) => {
+        buf.clear();
+        buf
+      },
+      None => BytesMut::with_capacity(self.buffer_capacity)
+    }
+  }
+
+  /// Return a buffer to the pool.
+  pub fn checkin(&self, buf: BytesMut) {
+    let mut buffers = self.lock();
+
+    if buffers.len() < self.max_buffers {
+      buffers.push(buf);
+    }
+  }
+
+  /// Read the number of idle buffers in the pool.
+  pub fn len(&self) -> usize {
+    self.lock().len()
+  }
+
+  /// Whether or not the pool has no idle buffers.
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  fn lock(&self) -> MutexGuard<Vec<BytesMut>> {
+    // the buffers are cleared on checkout, so a panic while the lock is held can't leave them in a bad state
+    self.buffers.lock().unwrap_or_else(|e| e.into_inner())
+  }
+
+}
+
+/// Attempt to encode a frame into a buffer taken from `pool`, returning the encoded bytes and returning the buffer to the pool.
+///
+/// The encoded bytes are split off the front of the pooled buffer without copying, and the rest of the buffer's capacity is
+/// returned to the pool for the next frame. A new allocation is only made once the remaining capacity is smaller than the
+/// frame that was just encoded.
+pub fn encode_pooled(pool: &EncodeBufferPool, frame: &Frame) -> Result<Bytes, RedisProtocolError<'static>> {
+  let mut buf = pool.checkout();
+
+  let result = encode_bytes(&mut buf, frame)
+    .map_err(|e| e.into_owned())
+    .map(|len| buf.split_to(len).freeze());
+
+  if let Ok(ref encoded) = result {
+    buf.reserve(encoded.len());
+  }
+  pool.checkin(buf);
+  result
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
   use ::utils::*;
   use ::types::*;
 
+  use std::f64;
+  use std::io::Result as IoResult;
+
+  struct FailingWriter;
+
+  impl Write for FailingWriter {
+    fn write(&mut self, _: &[u8]) -> IoResult<usize> {
+      Err(IoError::new(IoErrorKind::BrokenPipe, "closed"))
+    }
+
+    fn flush(&mut self) -> IoResult<()> {
+      Ok(())
+    }
+  }
+
   const PADDING: &'static str = "foobar";
 
   fn str_to_bytes(s: &str) -> Vec<u8> {
@@ -266,6 +708,30 @@ mod tests {
     encode_and_verify_non_empty(&input, expected);
   }
 
+  #[test]
+  fn should_encode_small_array_header_from_table() {
+    assert_eq!(small_array_header(3), Some(&b"*3\r\n"[..]));
+    assert_eq!(small_array_header(16), Some(&b"*16\r\n"[..]));
+    assert_eq!(small_array_header(17), None);
+    assert_eq!(small_array_header(100), None);
+
+    for len in 0..SMALL_ARRAY_HEADERS.len() {
+      assert_eq!(small_array_header(len).unwrap(), format!("*{}\r\n", len).as_bytes());
+    }
+
+    let input = Frame::Array(vec![Frame::Null; 3]);
+    encode_and_verify_empty(&input, "*3\r\n$-1\r\n$-1\r\n$-1\r\n");
+  }
+
+  #[test]
+  fn should_encode_large_array_header_without_table() {
+    let input = Frame::Array(vec![Frame::Null; 100]);
+    let expected = format!("*100\r\n{}", "$-1\r\n".repeat(100));
+
+    encode_and_verify_empty(&input, &expected);
+    encode_and_verify_non_empty(&input, &expected);
+  }
+
   #[test]
   fn should_encode_array_null_test() {
     let expected = "*3\r\n$4\r\nHSET\r\n$3\r\nfoo\r\n$-1\r\n";
@@ -381,6 +847,486 @@ mod tests {
     encode_and_verify_non_empty(&i1_input, i1_expected);
   }
 
+  #[test]
+  fn should_encode_null() {
+    let expected = "$-1\r\n";
+    let input = Frame::Null;
+
+    encode_and_verify_empty(&input, expected);
+    encode_and_verify_non_empty(&input, expected);
+  }
+
+  #[test]
+  fn should_encode_resp3_null() {
+    let expected = "_\r\n";
+    let input = Frame::NullResp3;
+
+    encode_and_verify_empty(&input, expected);
+    encode_and_verify_non_empty(&input, expected);
+  }
+
+  #[test]
+  fn should_encode_array_resp3_null_test() {
+    let expected = "*2\r\n$3\r\nfoo\r\n_\r\n";
+    let input = Frame::Array(vec![
+      Frame::BulkString(str_to_bytes("foo")),
+      Frame::NullResp3
+    ]);
+
+    encode_and_verify_empty(&input, expected);
+    encode_and_verify_non_empty(&input, expected);
+  }
+
+  #[test]
+  fn should_encode_array_of_any_frame_kind() {
+    let expected = "*5\r\n$9\r\nsubscribe\r\n$3\r\nfoo\r\n:1\r\n+OK\r\n%1\r\n#t\r\n,1.5\r\n";
+    let input = Frame::Array(vec![
+      Frame::BulkString(str_to_bytes("subscribe")),
+      Frame::BulkString(str_to_bytes("foo")),
+      Frame::Integer(1),
+      Frame::SimpleString("OK".into()),
+      Frame::Map(vec![(Frame::Boolean(true), Frame::Double(1.5))])
+    ]);
+
+    encode_and_verify_empty(&input, expected);
+    encode_and_verify_non_empty(&input, expected);
+  }
+
+  #[test]
+  fn should_reencode_decoded_pubsub_reply() {
+    let bytes = b"*3\r\n$9\r\nsubscribe\r\n$3\r\nfoo\r\n:1\r\n";
+    let (frame, _) = ::decode::decode(bytes).unwrap();
+
+    assert_eq!(&encode_to_bytes(&frame.unwrap()).unwrap()[..], &bytes[..]);
+  }
+
+  fn encode_and_decode_double(d: f64) {
+    let mut buf = empty_bytes();
+    let _ = encode_bytes(&mut buf, &Frame::Double(d)).unwrap();
+
+    match ::decode::decode(&buf).unwrap() {
+      (Some(Frame::Double(decoded)), len) => {
+        assert_eq!(decoded.to_bits(), d.to_bits(), "decoded double matches");
+        assert_eq!(len, buf.len(), "decoded double len matches");
+      },
+      f => panic!("Expected double, found {:?}", f)
+    }
+  }
+
+  #[test]
+  fn should_encode_double() {
+    let expected = ",3.141592653589793\r\n";
+    let input = Frame::Double(f64::consts::PI);
+
+    encode_and_verify_empty(&input, expected);
+    encode_and_verify_non_empty(&input, expected);
+  }
+
+  #[test]
+  fn should_encode_whole_number_double_without_fraction() {
+    encode_and_verify_empty(&Frame::Double(3.0), ",3\r\n");
+    encode_and_verify_non_empty(&Frame::Double(3.0), ",3\r\n");
+    encode_and_verify_empty(&Frame::Double(-12.0), ",-12\r\n");
+
+    let (frame, _) = ::decode::decode(b",3\r\n").unwrap();
+    assert_eq!(encode_to_bytes(&frame.unwrap()).unwrap(), Bytes::from(",3\r\n"));
+  }
+
+  #[test]
+  fn should_roundtrip_fractional_double_unchanged() {
+    let (frame, len) = ::decode::decode(b",3.5\r\n").unwrap();
+    let frame = frame.unwrap();
+
+    assert_eq!(frame, Frame::Double(3.5));
+    assert_eq!(len, 6);
+    assert_eq!(encode_to_bytes(&frame).unwrap(), Bytes::from(",3.5\r\n"));
+  }
+
+  #[test]
+  fn should_encode_double_inf_and_nan() {
+    encode_and_verify_empty(&Frame::Double(f64::INFINITY), ",inf\r\n");
+    encode_and_verify_empty(&Frame::Double(f64::NEG_INFINITY), ",-inf\r\n");
+    encode_and_verify_empty(&Frame::Double(f64::NAN), ",nan\r\n");
+  }
+
+  #[test]
+  fn should_encode_and_decode_doubles_exactly() {
+    encode_and_decode_double(f64::consts::PI);
+    encode_and_decode_double(1e100);
+    encode_and_verify_empty(&Frame::Double(1e100), ",1e100\r\n");
+    encode_and_decode_double(0.1);
+    encode_and_decode_double(-2.5e-8);
+  }
+
+  #[test]
+  fn should_encode_large_and_small_doubles_with_exponent() {
+    encode_and_verify_empty(&Frame::Double(1e100), ",1e100\r\n");
+    encode_and_verify_empty(&Frame::Double(-2.5e-8), ",-2.5e-8\r\n");
+    encode_and_verify_empty(&Frame::Double(1e16), ",1e16\r\n");
+    encode_and_verify_empty(&Frame::Double(1.5e300), ",1.5e300\r\n");
+    encode_and_verify_empty(&Frame::Double(f64::MIN_POSITIVE), ",2.2250738585072014e-308\r\n");
+    encode_and_verify_empty(&Frame::Double(f64::from_bits(1)), ",5e-324\r\n");
+  }
+
+  #[test]
+  fn should_encode_doubles_near_exponent_thresholds_without_exponent() {
+    encode_and_verify_empty(&Frame::Double(1e15), ",1000000000000000\r\n");
+    encode_and_verify_empty(&Frame::Double(9007199254740993.0), ",9007199254740992\r\n");
+    encode_and_verify_empty(&Frame::Double(0.00001), ",0.00001\r\n");
+    encode_and_verify_empty(&Frame::Double(0.1), ",0.1\r\n");
+  }
+
+  #[test]
+  fn should_encode_negative_zero_with_sign() {
+    encode_and_verify_empty(&Frame::Double(-0.0), ",-0\r\n");
+    encode_and_verify_empty(&Frame::Double(0.0), ",0\r\n");
+    encode_and_decode_double(-0.0);
+  }
+
+  #[test]
+  fn should_encode_and_decode_subnormal_doubles_exactly() {
+    encode_and_decode_double(f64::MIN_POSITIVE);
+    encode_and_decode_double(f64::MIN_POSITIVE / 3.0);
+    encode_and_decode_double(-f64::from_bits(1));
+  }
+
+  #[test]
+  fn should_encode_pooled() {
+    let pool = EncodeBufferPool::new(2);
+    assert!(pool.is_empty());
+
+    for i in 0..100 {
+      let frame = Frame::BulkString(i.to_string().into_bytes());
+      let expected = format!("${}\r\n{}\r\n", i.to_string().len(), i);
+
+      assert_eq!(encode_pooled(&pool, &frame).unwrap(), Bytes::from(expected));
+      assert_eq!(pool.len(), 1);
+    }
+  }
+
+  #[test]
+  fn should_encode_pooled_without_allocating() {
+    let pool = EncodeBufferPool::with_buffer_capacity(1, 1024);
+    let frame = Frame::BulkString(vec![b'a'; 100]);
+
+    let first = encode_pooled(&pool, &frame).unwrap();
+    let mut previous = first.clone();
+
+    for _ in 0..8 {
+      let encoded = encode_pooled(&pool, &frame).unwrap();
+
+      assert_eq!(encoded, first);
+      assert_eq!(encoded.as_ptr() as usize, previous.as_ptr() as usize + previous.len());
+      previous = encoded;
+    }
+    assert_eq!(pool.len(), 1);
+  }
+
+  #[test]
+  fn should_reserve_pooled_buffer_capacity() {
+    let pool = EncodeBufferPool::with_buffer_capacity(1, 256);
+    let frame = Frame::BulkString(vec![b'a'; 100]);
+
+    let first = encode_pooled(&pool, &frame).unwrap();
+    let second = encode_pooled(&pool, &frame).unwrap();
+    assert_eq!(second.as_ptr() as usize, first.as_ptr() as usize + first.len());
+
+    // the remaining capacity is too small for another frame, so the buffer was moved to a new allocation
+    let buf = pool.checkout();
+    assert!(buf.capacity() >= first.len());
+    assert_ne!(buf.as_ptr() as usize, second.as_ptr() as usize + second.len());
+    pool.checkin(buf);
+
+    assert_eq!(encode_pooled(&pool, &frame).unwrap(), first);
+    assert_eq!(&second[..], &first[..]);
+  }
+
+  #[test]
+  fn should_bound_encode_pool_size() {
+    let pool = EncodeBufferPool::new(2);
+    let buffers: Vec<BytesMut> = (0..5).map(|_| pool.checkout()).collect();
+
+    for buf in buffers.into_iter() {
+      pool.checkin(buf);
+    }
+    assert_eq!(pool.len(), 2);
+
+    let mut buf = pool.checkout();
+    buf.extend_from_slice(b"foo");
+    pool.checkin(buf);
+    assert!(pool.checkout().is_empty());
+  }
+
+  #[test]
+  fn should_write_frame_to_writer() {
+    let mut out = Vec::new();
+    let frame = Frame::Array(vec![Frame::BulkString(str_to_bytes("GET")), Frame::BulkString(str_to_bytes("foo"))]);
+
+    let len = write_frame_to(&mut out, &frame).unwrap();
+    assert_eq!(len, 22);
+    assert_eq!(out, b"*2\r\n$3\r\nGET\r\n$3\r\nfoo\r\n".to_vec());
+
+    let len = write_frame_to(&mut out, &Frame::Integer(1)).unwrap();
+    assert_eq!(len, 4);
+    assert_eq!(&out[22..], b":1\r\n");
+  }
+
+  #[test]
+  fn should_return_writer_errors_writing_frame() {
+    let e = write_frame_to(&mut FailingWriter, &Frame::Integer(1)).unwrap_err();
+    assert_eq!(e.kind(), IoErrorKind::BrokenPipe);
+  }
+
+  #[test]
+  fn should_encode_length_prefixed() {
+    let mut buf = empty_bytes();
+    let input = Frame::BulkString(str_to_bytes("foo"));
+
+    let len = encode_length_prefixed(&mut buf, &input).unwrap();
+    assert_eq!(len, 13);
+    assert_eq!(buf, "\x00\x00\x00\x09$3\r\nfoo\r\n".as_bytes());
+  }
+
+  #[test]
+  fn should_encode_boolean() {
+    encode_and_verify_empty(&Frame::Boolean(true), "#t\r\n");
+    encode_and_verify_non_empty(&Frame::Boolean(true), "#t\r\n");
+    encode_and_verify_empty(&Frame::Boolean(false), "#f\r\n");
+    encode_and_verify_non_empty(&Frame::Boolean(false), "#f\r\n");
+  }
+
+  #[test]
+  fn should_encode_and_decode_empty_simplestring_and_error() {
+    for (frame, expected) in [(Frame::SimpleString(String::new()), "+\r\n"), (Frame::Error(String::new()), "-\r\n")].iter() {
+      encode_and_verify_empty(frame, expected);
+      encode_and_verify_non_empty(frame, expected);
+
+      let encoded = encode_to_bytes(frame).unwrap();
+      assert_eq!(::decode::decode(&encoded).unwrap(), (Some(frame.clone()), 3));
+    }
+  }
+
+  #[test]
+  fn should_encode_verbatimstring() {
+    let input = Frame::VerbatimString { format: VerbatimFormat::Txt, data: str_to_bytes("Some string") };
+    encode_and_verify_empty(&input, "=15\r\ntxt:Some string\r\n");
+    encode_and_verify_non_empty(&input, "=15\r\ntxt:Some string\r\n");
+
+    let input = Frame::VerbatimString { format: VerbatimFormat::Other(*b"abc"), data: vec![] };
+    encode_and_verify_empty(&input, "=4\r\nabc:\r\n");
+  }
+
+  #[test]
+  fn should_encode_bignumber() {
+    let expected = "(3492890328409238509324850943850943825024385\r\n";
+    let input = Frame::BigNumber("3492890328409238509324850943850943825024385".into());
+
+    encode_and_verify_empty(&input, expected);
+    encode_and_verify_non_empty(&input, expected);
+  }
+
+  #[test]
+  fn should_write_integers() {
+    let mut buf = BytesMut::new();
+
+    write_integer(&mut buf, 123);
+    assert_eq!(&buf[..], b":123\r\n");
+
+    buf.clear();
+    write_integer(&mut buf, i64::MIN);
+    assert_eq!(&buf[..], b":-9223372036854775808\r\n");
+
+    buf.clear();
+    write_integer(&mut buf, i64::MAX);
+    assert_eq!(&buf[..], b":9223372036854775807\r\n");
+  }
+
+  #[test]
+  fn should_append_integers_matching_frame_encoding() {
+    let mut buf = BytesMut::new();
+    let mut expected = BytesMut::new();
+
+    for i in &[i64::MIN, -1000, -10, -1, 0, 5, 10, 99, 1000000, i64::MAX] {
+      write_integer(&mut buf, *i);
+      let _ = encode_bytes(&mut expected, &Frame::Integer(*i)).unwrap();
+    }
+
+    assert_eq!(buf, expected);
+  }
+
+  #[test]
+  fn should_encode_routed_command() {
+    let mut buf = BytesMut::from("+OK\r\n");
+    let frame = Frame::Array(vec![
+      Frame::BulkString("GET".into()),
+      Frame::BulkString("foo".into())
+    ]);
+
+    let (len, slot) = encode_routed(&mut buf, &frame, KeySpec::new(1, 1, 1)).unwrap();
+
+    assert_eq!(len, 22);
+    assert_eq!(&buf[5..], b"*2\r\n$3\r\nGET\r\n$3\r\nfoo\r\n");
+    assert_eq!(slot, Some(utils::redis_keyslot_bytes(b"foo")));
+    assert_eq!(slot, Some(12182));
+  }
+
+  #[test]
+  fn should_encode_routed_command_without_keys() {
+    let mut buf = BytesMut::new();
+    let frame = Frame::Array(vec![Frame::BulkString("PING".into())]);
+
+    let (len, slot) = encode_routed(&mut buf, &frame, KeySpec::new(0, 0, 0)).unwrap();
+
+    assert_eq!(len, buf.len());
+    assert_eq!(slot, None);
+  }
+
+  #[test]
+  fn should_not_encode_routed_command_with_cross_slot_keys() {
+    let mut buf = BytesMut::new();
+    let frame = Frame::Array(vec![
+      Frame::BulkString("MGET".into()),
+      Frame::BulkString("foo".into()),
+      Frame::BulkString("bar".into())
+    ]);
+
+    let e = encode_routed(&mut buf, &frame, KeySpec::new(1, -1, 1)).unwrap_err();
+
+    assert_eq!(e.kind(), &RedisProtocolErrorKind::EncodeError);
+    assert!(e.to_string().starts_with("Encode Error: Keys map to different slots"));
+    assert!(buf.is_empty());
+  }
+
+  #[test]
+  fn should_encode_array_of_raw_elements() {
+    let mut buf = BytesMut::new();
+    let raw = [Bytes::from(":1\r\n"), Bytes::from(":2\r\n")];
+
+    let len = encode_array_of_raw(&mut buf, 2, &raw).unwrap();
+    assert_eq!(&buf[..], &b"*2\r\n:1\r\n:2\r\n"[..]);
+    assert_eq!(len, 12);
+
+    let expected = (Some(Frame::Array(vec![Frame::Integer(1), Frame::Integer(2)])), 12);
+    assert_eq!(::decode::decode(&buf).unwrap(), expected);
+  }
+
+  #[test]
+  fn should_encode_large_array_of_raw_elements() {
+    let mut buf = BytesMut::from("+OK\r\n");
+    let raw = vec![Bytes::from("$-1\r\n"); 20];
+
+    assert_eq!(encode_array_of_raw(&mut buf, 20, &raw).unwrap(), 5 + 5 + 100);
+    assert_eq!(&buf[5..10], &b"*20\r\n"[..]);
+  }
+
+  #[test]
+  fn should_error_encoding_array_of_raw_with_wrong_count() {
+    let mut buf = BytesMut::new();
+    let e = encode_array_of_raw(&mut buf, 3, &[Bytes::from(":1\r\n")]).unwrap_err();
+
+    assert_eq!(e.kind(), &RedisProtocolErrorKind::EncodeError);
+    assert!(buf.is_empty());
+  }
+
+  #[test]
+  fn should_write_streamed_string() {
+    let mut buf = BytesMut::new();
+    write_stream_start(&mut buf);
+    write_bulk_chunk(&mut buf, b"foo");
+    write_bulk_chunk(&mut buf, b"");
+    write_bulk_chunk(&mut buf, b"barbaz");
+    write_stream_end(&mut buf);
+
+    assert_eq!(&buf[..], &b"$?\r\n;3\r\nfoo\r\n;6\r\nbarbaz\r\n;0\r\n"[..]);
+
+    let (frame, len) = ::decode::decode_bytes(&buf).unwrap();
+    assert_eq!(frame, Some(Frame::BulkString("foobarbaz".into())));
+    assert_eq!(len, buf.len());
+  }
+
+  #[test]
+  fn should_encode_map() {
+    let frame = Frame::Map(vec![
+      (Frame::SimpleString("foo".into()), Frame::Integer(1)),
+      (Frame::BulkString("bar".into()), Frame::Array(vec![Frame::Null]))
+    ]);
+    let expected = "%2\r\n+foo\r\n:1\r\n$3\r\nbar\r\n*1\r\n$-1\r\n";
+
+    encode_and_verify_empty(&frame, expected);
+    encode_and_verify_non_empty(&frame, expected);
+  }
+
+  #[test]
+  fn should_encode_set_push_and_attribute() {
+    let frame = Frame::Set(vec![Frame::Integer(1), Frame::Boolean(false)]);
+    encode_and_verify_empty(&frame, "~2\r\n:1\r\n#f\r\n");
+    encode_and_verify_non_empty(&frame, "~2\r\n:1\r\n#f\r\n");
+
+    let frame = Frame::Push(vec![Frame::SimpleString("message".into()), Frame::BulkString("foo".into())]);
+    encode_and_verify_empty(&frame, ">2\r\n+message\r\n$3\r\nfoo\r\n");
+    encode_and_verify_non_empty(&frame, ">2\r\n+message\r\n$3\r\nfoo\r\n");
+
+    let frame = Frame::Attribute(vec![(Frame::SimpleString("ttl".into()), Frame::Integer(3600))]);
+    encode_and_verify_empty(&frame, "|1\r\n+ttl\r\n:3600\r\n");
+    encode_and_verify_non_empty(&frame, "|1\r\n+ttl\r\n:3600\r\n");
+  }
+
+  #[test]
+  fn should_encode_many() {
+    let mut buf = BytesMut::new();
+    let frames = Frame::transaction(vec![Frame::Array(vec![Frame::BulkString("PING".into())])]);
+
+    let len = encode_many(&mut buf, &frames).unwrap();
+    assert_eq!(len, buf.len());
+    assert_eq!(&buf[..], &b"*1\r\n$5\r\nMULTI\r\n*1\r\n$4\r\nPING\r\n*1\r\n$4\r\nEXEC\r\n"[..]);
+  }
+
+  #[test]
+  fn should_encode_small_integers() {
+    encode_and_verify_empty(&Frame::Integer(1), ":1\r\n");
+    encode_and_verify_non_empty(&Frame::Integer(1), ":1\r\n");
+
+    for i in -2..11 {
+      let expected = format!(":{}\r\n", i);
+      encode_and_verify_empty(&Frame::Integer(i), &expected);
+      encode_and_verify_non_empty(&Frame::Integer(i), &expected);
+    }
+  }
+
+  #[test]
+  fn should_encode_to_bytes() {
+    let expected = "*1\r\n$4\r\nPING\r\n";
+    let input = Frame::Array(vec![
+      Frame::BulkString(str_to_bytes("PING"))
+    ]);
+
+    let bytes = encode_to_bytes(&input).unwrap();
+    assert_eq!(bytes, Bytes::from(expected.as_bytes()));
+    assert_eq!(input.encode_to_bytes().unwrap(), bytes);
+  }
+
+  #[test]
+  fn should_encode_to_bytes_at_power_of_ten_boundaries() {
+    let mut value: i64 = 9;
+
+    loop {
+      for i in [value, value + 1, -value, -(value + 1)].iter() {
+        let expected = format!(":{}\r\n", i);
+        assert_eq!(encode_to_bytes(&Frame::Integer(*i)).unwrap(), Bytes::from(expected.as_bytes()));
+      }
+
+      if value > i64::MAX / 10 {
+        break;
+      }
+      value = value * 10 + 9;
+    }
+
+    let expected = format!(":{}\r\n", i64::MAX);
+    assert_eq!(encode_to_bytes(&Frame::Integer(i64::MAX)).unwrap(), Bytes::from(expected.as_bytes()));
+    let expected = format!(":{}\r\n", i64::MIN);
+    assert_eq!(encode_to_bytes(&Frame::Integer(i64::MIN)).unwrap(), Bytes::from(expected.as_bytes()));
+  }
+
   #[test]
   fn should_encode_negative_integer() {
     let i2_expected = ":-1000\r\n";
@@ -0,0 +1,108 @@
+
+use rand::Rng;
+use rand::distributions::Alphanumeric;
+
+use types::*;
+use encode::encode_to_bytes;
+use decode::decode;
+
+/// The maximum number of elements or pairs in a generated aggregate frame.
+pub const MAX_AGGREGATE_LEN: usize = 8;
+/// The maximum length of a generated string payload.
+pub const MAX_STRING_LEN: usize = 32;
+
+fn random_string<R: Rng>(rng: &mut R) -> String {
+  let len = rng.gen_range(0, MAX_STRING_LEN + 1);
+  rng.sample_iter(&Alphanumeric).take(len).collect()
+}
+
+fn random_bytes<R: Rng>(rng: &mut R) -> Vec<u8> {
+  let len = rng.gen_range(0, MAX_STRING_LEN + 1);
+  (0..len).map(|_| rng.gen::<u8>()).collect()
+}
+
+fn random_scalar<R: Rng>(rng: &mut R) -> Frame {
+  match rng.gen_range(0, 10) {
+    0 => Frame::SimpleString(random_string(rng)),
+    1 => Frame::Error(format!("ERR {}", random_string(rng))),
+    2 => Frame::Integer(rng.gen::<i64>()),
+    3 => Frame::BulkString(random_bytes(rng)),
+    4 => Frame::Null,
+    5 => Frame::NullResp3,
+    6 => Frame::Double(rng.gen::<f64>() * rng.gen::<i32>() as f64),
+    7 => Frame::Boolean(rng.gen::<bool>()),
+    8 => {
+      let format = [rng.gen_range(b'a', b'z' + 1), rng.gen_range(b'a', b'z' + 1), rng.gen_range(b'a', b'z' + 1)];
+      Frame::VerbatimString { format: VerbatimFormat::from_bytes(format), data: random_bytes(rng) }
+    },
+    _ => {
+      let digits: String = (0..rng.gen_range(1, 50)).map(|_| (b'0' + rng.gen_range(0, 10)) as char).collect();
+      Frame::BigNumber(if rng.gen::<bool>() { format!("-{}", digits) }else{ digits })
+    }
+  }
+}
+
+fn random_frames<R: Rng>(rng: &mut R, max_depth: usize) -> Vec<Frame> {
+  let len = rng.gen_range(0, MAX_AGGREGATE_LEN + 1);
+  (0..len).map(|_| random_frame(rng, max_depth - 1)).collect()
+}
+
+fn random_pairs<R: Rng>(rng: &mut R, max_depth: usize) -> Vec<(Frame, Frame)> {
+  let len = rng.gen_range(0, MAX_AGGREGATE_LEN + 1);
+  (0..len).map(|_| (random_scalar(rng), random_frame(rng, max_depth - 1))).collect()
+}
+
+/// Generate a random frame that decodes back to itself, nesting aggregate frames at most `max_depth` levels deep.
+///
+/// Aggregates have at most `MAX_AGGREGATE_LEN` elements and string payloads at most `MAX_STRING_LEN` bytes. Errors are never
+/// cluster redirections.
+pub fn random_frame<R: Rng>(rng: &mut R, max_depth: usize) -> Frame {
+  if max_depth == 0 || rng.gen_range(0, 3) > 0 {
+    return random_scalar(rng);
+  }
+
+  match rng.gen_range(0, 5) {
+    0 => Frame::Array(random_frames(rng, max_depth)),
+    1 => Frame::Set(random_frames(rng, max_depth)),
+    2 => Frame::Push(random_frames(rng, max_depth)),
+    3 => Frame::Map(random_pairs(rng, max_depth)),
+    _ => Frame::Attribute(random_pairs(rng, max_depth))
+  }
+}
+
+/// Encode and decode `frame`, panicking if the decoded frame differs or not all the encoded bytes are consumed.
+pub fn assert_roundtrip(frame: &Frame) {
+  let encoded = encode_to_bytes(frame).expect("Failed to encode frame");
+  let (decoded, len) = decode(&encoded).expect("Failed to decode frame");
+
+  assert_eq!(decoded.as_ref(), Some(frame), "decoded frame matches");
+  assert_eq!(len, encoded.len(), "decoded frame len matches");
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use rand::{SeedableRng, XorShiftRng};
+
+  #[test]
+  fn should_roundtrip_random_frames() {
+    let mut rng = XorShiftRng::from_seed([7; 16]);
+
+    for _ in 0..5000 {
+      assert_roundtrip(&random_frame(&mut rng, 4));
+    }
+  }
+
+  #[test]
+  fn should_respect_max_depth() {
+    let mut rng = XorShiftRng::from_seed([3; 16]);
+
+    for _ in 0..1000 {
+      match random_frame(&mut rng, 0) {
+        Frame::Array(_) | Frame::Set(_) | Frame::Push(_) | Frame::Map(_) | Frame::Attribute(_) => panic!("Expected scalar frame"),
+        _ => {}
+      };
+    }
+  }
+
+}
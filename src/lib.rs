@@ -49,6 +49,16 @@ extern crate pretty_env_logger;
 extern crate bytes;
 extern crate crc16;
 
+#[cfg(feature = "json")]
+#[cfg_attr(test, macro_use)]
+extern crate serde_json;
+
+#[cfg(feature = "async")]
+extern crate futures;
+
+#[cfg(feature = "test-util")]
+extern crate rand;
+
 #[macro_use]
 extern crate cookie_factory;
 #[macro_use]
@@ -62,20 +72,36 @@ pub mod types;
 pub mod encode;
 /// Decoding functions for BytesMut and slices.
 pub mod decode;
+/// A `Stream` adapter for decoding frames from an `AsyncRead`.
+#[cfg(feature = "async")]
+pub mod stream;
+/// Random frame generation for property testing.
+#[cfg(feature = "test-util")]
+pub mod gen;
 
 /// Shorthand for `use`'ing `types`, `encode`, `decode`, etc.
 pub mod prelude {
   pub use types::*;
   pub use encode::*;
   pub use decode::*;
+  #[cfg(feature = "async")]
+  pub use stream::*;
 
-  pub use utils::redis_keyslot;
+  pub use utils::{redis_keyslot, redis_keyslot_bytes, Crc16};
+  #[cfg(feature = "redirection-cache")]
+  pub use utils::RedirectionCache;
 }
 
 pub use utils::{
   redis_keyslot,
+  redis_keyslot_bytes,
+  Crc16,
   digits_in_number,
   ZEROED_KB,
   CRLF,
-  NULL
+  NULL,
+  RESP3_NULL
 };
+
+#[cfg(feature = "redirection-cache")]
+pub use utils::{RedirectionCache, SHARED_REDIRECTION_CACHE_CAPACITY};
@@ -0,0 +1,176 @@
+
+use bytes::BytesMut;
+
+use futures::io::AsyncRead;
+use futures::stream::Stream;
+use futures::task::{Context, Poll};
+
+use std::io::ErrorKind as IoErrorKind;
+use std::pin::Pin;
+
+use types::*;
+use decode::decode;
+use utils;
+
+/// A `Stream` of frames decoded from an `AsyncRead`.
+///
+/// Bytes are buffered between polls so frames split across several reads are yielded once complete. After a decode error
+/// the buffered bytes are discarded and the stream ends, since the position of the next frame is unknown.
+pub struct FrameStream<R> {
+  reader: R,
+  buf: BytesMut,
+  eof: bool,
+  terminated: bool
+}
+
+impl<R: AsyncRead + Unpin> FrameStream<R> {
+
+  /// Create a new stream reading from `reader`.
+  pub fn new(reader: R) -> FrameStream<R> {
+    FrameStream {
+      reader,
+      buf: BytesMut::new(),
+      eof: false,
+      terminated: false
+    }
+  }
+
+  /// Read a reference to the bytes buffered but not yet decoded.
+  pub fn buffer(&self) -> &BytesMut {
+    &self.buf
+  }
+
+  /// Consume the stream, returning the underlying reader.
+  pub fn into_inner(self) -> R {
+    self.reader
+  }
+
+}
+
+impl<R: AsyncRead + Unpin> Stream for FrameStream<R> {
+  type Item = Result<Frame, RedisProtocolError<'static>>;
+
+  fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+    let this = &mut *self;
+    let mut chunk = [0; utils::KB];
+
+    if this.terminated {
+      return Poll::Ready(None);
+    }
+
+    loop {
+      match decode(&this.buf).map_err(|e| e.into_owned()) {
+        Ok((Some(frame), len)) => {
+          let _ = this.buf.split_to(len);
+          return Poll::Ready(Some(Ok(frame)));
+        },
+        Ok((None, _)) => {},
+        Err(e) => {
+          this.buf.clear();
+          this.terminated = true;
+          return Poll::Ready(Some(Err(e)));
+        }
+      };
+
+      if this.eof {
+        return if this.buf.is_empty() {
+          Poll::Ready(None)
+        }else{
+          this.buf.clear();
+          Poll::Ready(Some(Err(RedisProtocolError::new(RedisProtocolErrorKind::DecodeError, "Unexpected EOF."))))
+        };
+      }
+
+      match Pin::new(&mut this.reader).poll_read(cx, &mut chunk) {
+        Poll::Pending                   => return Poll::Pending,
+        Poll::Ready(Ok(0))              => this.eof = true,
+        Poll::Ready(Ok(read))           => this.buf.extend_from_slice(&chunk[0..read]),
+        Poll::Ready(Err(ref e)) if e.kind() == IoErrorKind::Interrupted => continue,
+        Poll::Ready(Err(e))             => return Poll::Ready(Some(Err(RedisProtocolError::new(RedisProtocolErrorKind::Unknown, e.to_string()))))
+      };
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use futures::executor::block_on;
+  use futures::stream::StreamExt;
+  use std::io::Error as IoError;
+
+  struct MockReader {
+    chunks: Vec<Vec<u8>>,
+    pending: bool
+  }
+
+  impl AsyncRead for MockReader {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context, buf: &mut [u8]) -> Poll<Result<usize, IoError>> {
+      if self.pending {
+        self.pending = false;
+        cx.waker().wake_by_ref();
+        return Poll::Pending;
+      }
+      self.pending = true;
+
+      if self.chunks.is_empty() {
+        return Poll::Ready(Ok(0));
+      }
+      let chunk = self.chunks.remove(0);
+      buf[0..chunk.len()].copy_from_slice(&chunk);
+      Poll::Ready(Ok(chunk.len()))
+    }
+  }
+
+  fn mock_reader(chunks: &[&str]) -> MockReader {
+    MockReader {
+      chunks: chunks.iter().map(|c| c.as_bytes().to_vec()).collect(),
+      pending: true
+    }
+  }
+
+  #[test]
+  fn should_stream_frames_across_partial_reads() {
+    let reader = mock_reader(&["*2\r\n$3", "\r\nfoo\r", "\n$3\r\nbar\r\n:4", "2", "\r\n"]);
+    let frames: Vec<_> = block_on(FrameStream::new(reader).collect());
+
+    let expected = vec![
+      Ok(Frame::Array(vec![Frame::BulkString("foo".into()), Frame::BulkString("bar".into())])),
+      Ok(Frame::Integer(42))
+    ];
+    assert_eq!(frames, expected);
+  }
+
+  #[test]
+  fn should_error_on_eof_mid_frame() {
+    let reader = mock_reader(&["+OK\r\n", "$3\r\nfo"]);
+    let frames: Vec<_> = block_on(FrameStream::new(reader).collect());
+
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0], Ok(Frame::SimpleString("OK".into())));
+    assert_eq!(frames[1].as_ref().unwrap_err().kind(), &RedisProtocolErrorKind::DecodeError);
+  }
+
+  #[test]
+  fn should_end_stream_after_malformed_frame() {
+    let reader = mock_reader(&["+OK\r\n", "$foo\r\n", ":1\r\n"]);
+    let mut stream = FrameStream::new(reader);
+
+    assert_eq!(block_on(stream.next()), Some(Ok(Frame::SimpleString("OK".into()))));
+    assert_eq!(block_on(stream.next()).unwrap().unwrap_err().kind(), &RedisProtocolErrorKind::DecodeError);
+    assert!(stream.buffer().is_empty());
+    assert_eq!(block_on(stream.next()), None);
+    assert_eq!(block_on(stream.next()), None);
+  }
+
+  #[test]
+  fn should_collect_stream_with_malformed_frame() {
+    let reader = mock_reader(&["+OK\r\n$foo\r\n:1\r\n"]);
+    let frames: Vec<_> = block_on(FrameStream::new(reader).collect());
+
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0], Ok(Frame::SimpleString("OK".into())));
+    assert!(frames[1].is_err());
+  }
+
+}
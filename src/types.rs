@@ -1,18 +1,28 @@
 
 use ::utils;
+use ::encode;
 
 use std::fmt;
 use std::str;
+use std::cmp;
+use std::mem;
 use std::borrow::Cow;
 
 use std::error::Error;
 use std::borrow::Borrow;
+use std::net::SocketAddr;
+use std::collections::HashMap;
 
 use cookie_factory::GenError;
+use bytes::{
+  Bytes,
+  BytesMut
+};
 
 use nom::{
   Context,
   Err as NomError,
+  ErrorKind as NomErrorKind,
   Needed
 };
 
@@ -21,6 +31,17 @@ pub const ERROR_BYTE: u8        = b'-';
 pub const INTEGER_BYTE: u8      = b':';
 pub const BULKSTRING_BYTE: u8   = b'$';
 pub const ARRAY_BYTE: u8        = b'*';
+pub const RESP3_NULL_BYTE: u8   = b'_';
+pub const DOUBLE_BYTE: u8       = b',';
+pub const BOOLEAN_BYTE: u8      = b'#';
+pub const BIGNUMBER_BYTE: u8    = b'(';
+pub const VERBATIM_BYTE: u8     = b'=';
+pub const CHUNK_BYTE: u8        = b';';
+pub const STREAM_END_BYTE: u8   = b'.';
+pub const MAP_BYTE: u8          = b'%';
+pub const SET_BYTE: u8          = b'~';
+pub const PUSH_BYTE: u8         = b'>';
+pub const ATTRIBUTE_BYTE: u8    = b'|';
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum RedisProtocolErrorKind {
@@ -86,6 +107,15 @@ impl<'a> RedisProtocolError<'a> {
     }
   }
 
+  /// Convert the error into one that no longer borrows the underlying data, discarding the context.
+  pub fn into_owned(self) -> RedisProtocolError<'static> {
+    RedisProtocolError {
+      kind: self.kind,
+      desc: self.desc,
+      context: None
+    }
+  }
+
 }
 
 impl<'a> fmt::Display for RedisProtocolError<'a> {
@@ -125,6 +155,18 @@ impl<'a> From<NomError<&'a [u8]>> for RedisProtocolError<'a> {
         desc: Cow::Owned(format!("{:?}", e)),
         context: None
       }
+    }else if let Some((i, code)) = custom_nom_error(&e) {
+      let desc = if code == utils::LENGTH_OUT_OF_RANGE_ERROR {
+        Cow::Owned(utils::length_out_of_range_description(i))
+      }else{
+        Cow::Borrowed(utils::decode_error_description(code))
+      };
+
+      RedisProtocolError {
+        kind: RedisProtocolErrorKind::DecodeError,
+        desc,
+        context: Some(i)
+      }
     }else{
       let context = match e {
         NomError::Failure(Context::Code(i, _)) => Some(i),
@@ -141,6 +183,281 @@ impl<'a> From<NomError<&'a [u8]>> for RedisProtocolError<'a> {
   }
 }
 
+fn custom_nom_error<'a>(e: &NomError<&'a [u8]>) -> Option<(&'a [u8], u32)> {
+  match *e {
+    NomError::Failure(Context::Code(i, NomErrorKind::Custom(code))) => Some((i, code)),
+    NomError::Error(Context::Code(i, NomErrorKind::Custom(code)))   => Some((i, code)),
+    _ => None
+  }
+}
+
+/// Options used to configure the decoder.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DecodeOptions {
+  /// The maximum length of a simple string, error, or integer line, not including the trailing CRLF.
+  ///
+  /// Decoding fails with a `DecodeError` once this many bytes have been read without finding the end of the line. Defaults to `None`.
+  pub max_line_len: Option<usize>,
+  /// Whether to accept a bare LF, in addition to CRLF, as the terminator of a simple string, error, or integer line.
+  ///
+  /// Some servers truncate CRLF to LF under memory pressure. The consumed byte count only includes the bytes of the actual terminator. Defaults to `false`.
+  pub lenient_newlines: bool,
+  /// The maximum nesting depth of aggregate frames, where a top level array, map, set, push, or attribute has a depth of 1.
+  ///
+  /// Decoding fails with a `DecodeError` if any aggregate is nested deeper than this. Defaults to `None`.
+  pub max_depth: Option<usize>,
+  /// The maximum number of bytes a single top level frame can use, including all nested frames.
+  ///
+  /// Decoding fails with a `DecodeError` once this many bytes have been read without completing the frame, without
+  /// waiting for the rest of the frame to be received. Defaults to `None`.
+  pub max_total_size: Option<usize>,
+  /// Whether to reject sets that contain duplicate elements.
+  ///
+  /// Decoding fails with a `DecodeError` if any set contains two equal elements. Defaults to `false`.
+  pub strict_sets: bool,
+  /// Whether to decode `MOVED` and `ASK` errors as `Frame::Moved` and `Frame::Ask`.
+  ///
+  /// When `false` they are decoded as `Frame::Error` with the full error message. Defaults to `true`.
+  pub split_redirections: bool
+}
+
+impl Default for DecodeOptions {
+  fn default() -> Self {
+    DecodeOptions {
+      max_line_len: None,
+      lenient_newlines: false,
+      max_depth: None,
+      max_total_size: None,
+      strict_sets: false,
+      split_redirections: true
+    }
+  }
+}
+
+/// Counters describing the frames seen by `decode_with_stats`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DecodeStats {
+  /// The number of decoded frames of each kind. Only top level frames are counted, not the frames inside arrays.
+  pub frames: HashMap<FrameKind, usize>,
+  /// The total number of bytes consumed by decoded frames.
+  pub bytes_consumed: usize,
+  /// The number of decode attempts that found an incomplete frame.
+  pub incomplete: usize
+}
+
+impl DecodeStats {
+
+  /// Read the number of decoded frames of the provided kind.
+  pub fn frames(&self, kind: &FrameKind) -> usize {
+    self.frames.get(kind).cloned().unwrap_or(0)
+  }
+
+  /// Reset all counters to zero.
+  pub fn reset(&mut self) {
+    self.frames.clear();
+    self.bytes_consumed = 0;
+    self.incomplete = 0;
+  }
+
+}
+
+/// The result of `decode_hint`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DecodeHint {
+  /// A complete frame and the number of bytes consumed.
+  Complete(Frame, usize),
+  /// The buffer contains an incomplete frame. The inner value is the number of additional bytes needed to complete the
+  /// frame when it can be read from a length prefix, which is only the case for bulk strings.
+  NeedMore(Option<usize>)
+}
+
+/// The result of `decode_scalar`, which decodes simple replies without allocating.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ScalarFrame {
+  /// An integer.
+  Int(i64),
+  /// A simple string, such as `OK`, represented by its length.
+  StatusLen(u16),
+  /// A RESP2 or RESP3 null.
+  Null,
+  /// An error. The error message is not read.
+  Error
+}
+
+/// A version of the RESP protocol.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum RespVersion {
+  Resp2,
+  Resp3
+}
+
+/// The result of `next_frame_size`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FrameSize {
+  /// The total size of the next frame, including any bytes already in the buffer.
+  Known(usize),
+  /// The size of the next frame cannot be determined from the bytes seen so far.
+  Unknown
+}
+
+/// The expected shape of a reply, used by `decode_expecting` to validate decoded frames.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ReplyShape {
+  /// An integer.
+  Integer,
+  /// A bulk string or a null.
+  BulkOrNull,
+  /// A simple string.
+  Status,
+  /// An array or a null, such as the reply to an aborted `EXEC` or a `BLPOP` that timed out.
+  Array
+}
+
+impl ReplyShape {
+
+  /// Whether or not `frame` has this shape. Errors, including cluster redirections, match every shape.
+  pub fn matches(&self, frame: &Frame) -> bool {
+    if frame.is_error() {
+      return true;
+    }
+
+    match *self {
+      ReplyShape::Integer    => frame.kind() == FrameKind::Integer,
+      ReplyShape::BulkOrNull => frame.kind() == FrameKind::BulkString || frame.is_null(),
+      ReplyShape::Status     => frame.kind() == FrameKind::SimpleString,
+      ReplyShape::Array      => frame.kind() == FrameKind::Array || frame.is_null()
+    }
+  }
+
+}
+
+/// The result of `decode_streaming`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum StreamingFrame {
+  /// A complete frame.
+  Complete(Frame),
+  /// The start of a bulk string longer than the streaming threshold. The remaining bytes of the bulk string are read with the continuation.
+  BulkStringStreaming {
+    total_len: usize,
+    available: Bytes,
+    continuation: BulkStringContinuation
+  }
+}
+
+/// Whether the bytes and payload returned by `decode_raw_with_mode` are copied out of the source buffer or share its allocation.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CopyMode {
+  /// Copy the bytes and payload into a new allocation, so they do not keep the source buffer alive.
+  Owned,
+  /// Share the allocation of the source buffer without copying.
+  Borrowed
+}
+
+/// A decoded frame along with the original bytes from which it was decoded, returned by `decode_raw`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RawFrame {
+  /// The decoded frame.
+  pub frame: Frame,
+  /// The bytes of the frame as they were received.
+  pub bytes: Bytes,
+  /// The string payload of the frame, such as the contents of a bulk string, as a slice of `bytes`. This is `None` for frames
+  /// without a string payload and for streamed strings.
+  pub payload: Option<Bytes>
+}
+
+impl RawFrame {
+
+  /// Copy the original bytes of the frame into `out`, without re-encoding the frame.
+  pub fn forward(&self, out: &mut BytesMut) {
+    out.extend_from_slice(&self.bytes);
+  }
+
+}
+
+/// The state of a partially received bulk string returned by `decode_streaming`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BulkStringContinuation {
+  total_len: usize,
+  received: usize,
+  finished: bool
+}
+
+impl BulkStringContinuation {
+
+  pub fn new(total_len: usize, received: usize) -> BulkStringContinuation {
+    BulkStringContinuation { total_len, received, finished: false }
+  }
+
+  /// The length of the bulk string.
+  pub fn total_len(&self) -> usize {
+    self.total_len
+  }
+
+  /// The number of bytes of the bulk string received so far.
+  pub fn received(&self) -> usize {
+    self.received
+  }
+
+  /// Whether the bulk string and its trailing CRLF have been read.
+  pub fn is_finished(&self) -> bool {
+    self.finished
+  }
+
+  /// Split the next chunk of the bulk string off the front of `buf`.
+  ///
+  /// Returns `None` when `buf` is empty or once all the bytes of the bulk string have been read. In the latter case the
+  /// trailing CRLF is consumed as soon as it is available, after which `is_finished` returns `true`.
+  pub fn next_chunk(&mut self, buf: &mut BytesMut) -> Result<Option<Bytes>, RedisProtocolError<'static>> {
+    if self.finished {
+      return Ok(None);
+    }
+
+    let remaining = self.total_len - self.received;
+    if remaining == 0 {
+      if buf.len() < utils::CRLF.len() {
+        return Ok(None);
+      }
+      if &buf[0..utils::CRLF.len()] != utils::CRLF.as_bytes() {
+        return Err(RedisProtocolError::new(RedisProtocolErrorKind::DecodeError, "Expected CRLF after bulk string."));
+      }
+
+      let _ = buf.split_to(utils::CRLF.len());
+      self.finished = true;
+      return Ok(None);
+    }
+
+    if buf.is_empty() {
+      return Ok(None);
+    }
+
+    let chunk = buf.split_to(cmp::min(remaining, buf.len())).freeze();
+    self.received += chunk.len();
+    Ok(Some(chunk))
+  }
+
+}
+
+/// The positions of the keys in a command's arguments, mirroring the first key, last key, and step values returned by `COMMAND`.
+///
+/// Positions are indexes into the command array, where the command name is at position 0.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct KeySpec {
+  /// The position of the first key, or 0 if the command has no keys.
+  pub first_key: usize,
+  /// The position of the last key. Negative values count back from the end of the arguments, where -1 is the last argument.
+  pub last_key: isize,
+  /// The distance between consecutive keys.
+  pub step: usize
+}
+
+impl KeySpec {
+
+  pub fn new(first_key: usize, last_key: isize, step: usize) -> KeySpec {
+    KeySpec { first_key, last_key, step }
+  }
+
+}
+
 /// A cluster redirection message.
 ///
 /// <https://redis.io/topics/cluster-spec#redirection-and-resharding>
@@ -158,8 +475,77 @@ pub enum Redirection {
   }
 }
 
-/// An enum representing the kind of a Frame without references to any inner data.
+impl Redirection {
+
+  /// Parse the host and port of the redirection into a socket address.
+  ///
+  /// The host must be an IPv4 or IPv6 address, and IPv6 addresses may be wrapped in brackets.
+  pub fn socket_addr(&self) -> Result<SocketAddr, RedisProtocolError> {
+    let (host, port) = match *self {
+      Redirection::Moved { ref host, ref port, .. } => (host, *port),
+      Redirection::Ask { ref host, ref port, .. }   => (host, *port)
+    };
+
+    utils::host_to_socket_addr(host, port)
+  }
+
+}
+
+/// An error reply split into its error code and message.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ServerError {
+  /// The first word of the error, such as `ERR` or `WRONGTYPE`.
+  pub code: String,
+  /// The remainder of the error after the code, which may be empty.
+  pub message: String
+}
+
+/// A keyspace notification received as a publish-subscribe message.
+///
+/// <https://redis.io/topics/notifications>
 #[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeyspaceEvent {
+  /// The database index of the key.
+  pub db: u32,
+  /// The key that was modified.
+  pub key: String,
+  /// The event name, such as `set` or `expired`.
+  pub event: String
+}
+
+/// The format of a RESP3 verbatim string.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum VerbatimFormat {
+  /// Plain text, encoded as `txt`.
+  Txt,
+  /// Markdown, encoded as `mkd`.
+  Markdown,
+  /// Any other three byte format.
+  Other([u8; 3])
+}
+
+impl VerbatimFormat {
+
+  pub fn from_bytes(b: [u8; 3]) -> VerbatimFormat {
+    match &b {
+      b"txt" => VerbatimFormat::Txt,
+      b"mkd" => VerbatimFormat::Markdown,
+      _      => VerbatimFormat::Other(b)
+    }
+  }
+
+  pub fn to_bytes(&self) -> [u8; 3] {
+    match *self {
+      VerbatimFormat::Txt      => *b"txt",
+      VerbatimFormat::Markdown => *b"mkd",
+      VerbatimFormat::Other(b) => b
+    }
+  }
+
+}
+
+/// An enum representing the kind of a Frame without references to any inner data.
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub enum FrameKind {
   SimpleString,
   Error,
@@ -168,7 +554,17 @@ pub enum FrameKind {
   Array,
   Moved,
   Ask,
-  Null
+  Null,
+  /// A RESP3 null (`_\r\n`), kept distinct from the RESP2 null bulk string.
+  NullResp3,
+  Double,
+  Boolean,
+  BigNumber,
+  VerbatimString,
+  Map,
+  Set,
+  Push,
+  Attribute
 }
 
 impl FrameKind {
@@ -182,6 +578,15 @@ impl FrameKind {
       INTEGER_BYTE      => Some(Integer),
       BULKSTRING_BYTE   => Some(BulkString),
       ARRAY_BYTE        => Some(Array),
+      RESP3_NULL_BYTE   => Some(NullResp3),
+      DOUBLE_BYTE       => Some(Double),
+      BOOLEAN_BYTE      => Some(Boolean),
+      BIGNUMBER_BYTE    => Some(BigNumber),
+      VERBATIM_BYTE     => Some(VerbatimString),
+      MAP_BYTE          => Some(Map),
+      SET_BYTE          => Some(Set),
+      PUSH_BYTE         => Some(Push),
+      ATTRIBUTE_BYTE    => Some(Attribute),
       _                 => None
     }
   }
@@ -195,13 +600,36 @@ impl FrameKind {
       Integer             => INTEGER_BYTE,
       BulkString | Null   => BULKSTRING_BYTE,
       Array               => ARRAY_BYTE,
+      NullResp3           => RESP3_NULL_BYTE,
+      Double              => DOUBLE_BYTE,
+      Boolean             => BOOLEAN_BYTE,
+      BigNumber           => BIGNUMBER_BYTE,
+      VerbatimString      => VERBATIM_BYTE,
+      Map                 => MAP_BYTE,
+      Set                 => SET_BYTE,
+      Push                => PUSH_BYTE,
+      Attribute           => ATTRIBUTE_BYTE
+    }
+  }
+
+  /// Whether or not the frame kind only exists in RESP3.
+  pub fn is_resp3(&self) -> bool {
+    use self::FrameKind::*;
+
+    match *self {
+      NullResp3 | Double | Boolean | BigNumber | VerbatimString | Map | Set | Push | Attribute => true,
+      _ => false
     }
   }
 
 }
 
 /// An enum representing a Frame of data. Frames are recursively defined to account for arrays.
-#[derive(Clone, Debug, Eq, PartialEq)]
+///
+/// Frames have a total order, so they can be used as keys in a `BTreeMap`. Frames are ordered by their `FrameKind` and then
+/// by their contents, where strings and bytes are ordered lexicographically, aggregates are ordered element by element, and
+/// `NaN` doubles are equal to each other and greater than all other doubles.
+#[derive(Clone, Debug)]
 pub enum Frame {
   SimpleString(String),
   Error(String),
@@ -210,7 +638,29 @@ pub enum Frame {
   Array(Vec<Frame>),
   Moved(String),
   Ask(String),
-  Null
+  Null,
+  /// A RESP3 null. Decoding preserves which null form the peer used so it can be re-encoded the same way.
+  NullResp3,
+  /// A RESP3 double, encoded with the shortest representation that decodes back to the same value. Whole numbers are encoded
+  /// without a fractional part, such as `,3\r\n` for `3.0`.
+  Double(f64),
+  /// A RESP3 boolean.
+  Boolean(bool),
+  /// A RESP3 big number, stored as its base-10 digits with an optional leading `-`.
+  BigNumber(String),
+  /// A RESP3 verbatim string, which is a bulk string with a format such as plain text or markdown.
+  VerbatimString {
+    format: VerbatimFormat,
+    data: Vec<u8>
+  },
+  /// A RESP3 map, stored as key/value pairs in the order they were received.
+  Map(Vec<(Frame, Frame)>),
+  /// A RESP3 set.
+  Set(Vec<Frame>),
+  /// A RESP3 push, used for out of band data such as publish-subscribe messages.
+  Push(Vec<Frame>),
+  /// A RESP3 attribute map, which describes the reply that follows it.
+  Attribute(Vec<(Frame, Frame)>)
 }
 
 impl Frame {
@@ -225,6 +675,19 @@ impl Frame {
     }
   }
 
+  /// Read the oldest protocol version that can represent the frame, which is `Resp3` if the frame or any of its inner frames
+  /// is a RESP3 type.
+  pub fn resp_version(&self) -> RespVersion {
+    if self.kind().is_resp3() {
+      return RespVersion::Resp3;
+    }
+
+    match *self {
+      Frame::Array(ref frames) if frames.iter().any(|f| f.resp_version() == RespVersion::Resp3) => RespVersion::Resp3,
+      _ => RespVersion::Resp2
+    }
+  }
+
   /// Whether or not the frame represents a message on a publish-subscribe channel.
   pub fn is_pubsub_message(&self) -> bool {
     if let Frame::Array(ref frames) = *self {
@@ -234,6 +697,15 @@ impl Frame {
     }
   }
 
+  /// Whether or not the frame is the reply to a `SUBSCRIBE`, `UNSUBSCRIBE`, or similar command, such as
+  /// `["subscribe", "foo", 1]`. RESP3 confirmations are push frames, while RESP2 confirmations are arrays.
+  pub fn is_subscription_confirmation(&self) -> bool {
+    match *self {
+      Frame::Array(ref frames) | Frame::Push(ref frames) => utils::is_subscription_confirmation(frames),
+      _ => false
+    }
+  }
+
   /// Whether or not the frame represents a message on a publish-subscribe channel matched against a pattern subscription.
   pub fn is_pattern_pubsub_message(&self) -> bool {
     if let Frame::Array(ref frames) = *self {
@@ -253,7 +725,98 @@ impl Frame {
       Frame::Array(_)        => FrameKind::Array,
       Frame::Moved(_)        => FrameKind::Moved,
       Frame::Ask(_)          => FrameKind::Ask,
-      Frame::Null            => FrameKind::Null
+      Frame::Null            => FrameKind::Null,
+      Frame::NullResp3       => FrameKind::NullResp3,
+      Frame::Double(_)       => FrameKind::Double,
+      Frame::Boolean(_)      => FrameKind::Boolean,
+      Frame::BigNumber(_)    => FrameKind::BigNumber,
+      Frame::VerbatimString { .. } => FrameKind::VerbatimString,
+      Frame::Map(_)          => FrameKind::Map,
+      Frame::Set(_)          => FrameKind::Set,
+      Frame::Push(_)         => FrameKind::Push,
+      Frame::Attribute(_)    => FrameKind::Attribute
+    }
+  }
+
+  /// Read the human readable RESP type name for this frame, such as `"bulk string"`, for use in error and log messages.
+  ///
+  /// Cluster redirections are `"error"` and both null forms are `"null"`, since that is how they are sent on the wire.
+  pub fn redis_type_name(&self) -> &'static str {
+    match *self {
+      Frame::SimpleString(_)               => "simple string",
+      Frame::Error(_)
+        | Frame::Moved(_)
+        | Frame::Ask(_)                    => "error",
+      Frame::Integer(_)                    => "integer",
+      Frame::BulkString(_)                 => "bulk string",
+      Frame::Array(_)                      => "array",
+      Frame::Null | Frame::NullResp3       => "null",
+      Frame::Double(_)                     => "double",
+      Frame::Boolean(_)                    => "boolean",
+      Frame::BigNumber(_)                  => "big number",
+      Frame::VerbatimString { .. }         => "verbatim string",
+      Frame::Map(_)                        => "map",
+      Frame::Set(_)                        => "set",
+      Frame::Push(_)                       => "push",
+      Frame::Attribute(_)                  => "attribute"
+    }
+  }
+
+  /// Compare two frames, treating simple strings and bulk strings with the same contents as equal, both null forms as equal,
+  /// and empty arrays as equal to nulls. Aggregate frames are compared recursively.
+  ///
+  /// Prefer this over `==` when comparing replies from servers that may use either representation, such as in tests
+  /// against both RESP2 and RESP3 servers. Use `==` when the exact wire format matters.
+  pub fn loose_eq(&self, other: &Frame) -> bool {
+    let is_nullish = |f: &Frame| match *f {
+      Frame::Null | Frame::NullResp3 => true,
+      Frame::Array(ref frames)       => frames.is_empty(),
+      _                              => false
+    };
+    if is_nullish(self) && is_nullish(other) {
+      return true;
+    }
+
+    match (self, other) {
+      (&Frame::SimpleString(ref a), &Frame::BulkString(ref b))
+        | (&Frame::BulkString(ref b), &Frame::SimpleString(ref a)) => a.as_bytes() == &b[..],
+      (&Frame::Array(ref a), &Frame::Array(ref b))
+        | (&Frame::Set(ref a), &Frame::Set(ref b))
+        | (&Frame::Push(ref a), &Frame::Push(ref b)) => {
+        a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| a.loose_eq(b))
+      },
+      (&Frame::Map(ref a), &Frame::Map(ref b))
+        | (&Frame::Attribute(ref a), &Frame::Attribute(ref b)) => {
+        a.len() == b.len() && a.iter().zip(b.iter()).all(|((ak, av), (bk, bv))| ak.loose_eq(bk) && av.loose_eq(bv))
+      },
+      _ => self == other
+    }
+  }
+
+  /// Attempt to encode the frame in a canonical form, such that frames that are equal under `loose_eq` produce the same bytes.
+  ///
+  /// Before encoding, both null forms and empty arrays are replaced with `Null`, simple strings are replaced with bulk strings,
+  /// and the elements of sets, maps, and attributes are sorted, so sets and maps with the same elements in a different order
+  /// also produce the same bytes. Aggregate frames are normalized recursively.
+  pub fn canonical_bytes(&self) -> Result<Bytes, RedisProtocolError<'static>> {
+    encode::encode_to_bytes(&self.canonical()).map_err(|e| e.into_owned())
+  }
+
+  fn canonical(&self) -> Frame {
+    match *self {
+      Frame::NullResp3                              => Frame::Null,
+      Frame::Array(ref frames) if frames.is_empty() => Frame::Null,
+      Frame::SimpleString(ref s)                    => Frame::BulkString(s.as_bytes().to_vec()),
+      Frame::Array(ref frames)                      => Frame::Array(frames.iter().map(|f| f.canonical()).collect()),
+      Frame::Push(ref frames)                       => Frame::Push(frames.iter().map(|f| f.canonical()).collect()),
+      Frame::Set(ref frames)                        => {
+        let mut frames: Vec<Frame> = frames.iter().map(|f| f.canonical()).collect();
+        frames.sort();
+        Frame::Set(frames)
+      },
+      Frame::Map(ref pairs)                         => Frame::Map(canonical_pairs(pairs)),
+      Frame::Attribute(ref pairs)                   => Frame::Attribute(canonical_pairs(pairs)),
+      _                                             => self.clone()
     }
   }
 
@@ -261,12 +824,50 @@ impl Frame {
   pub fn as_str(&self) -> Option<&str> {
     match *self {
       Frame::BulkString(ref b)   => str::from_utf8(b).ok(),
+      Frame::VerbatimString { ref data, .. } => str::from_utf8(data).ok(),
       Frame::SimpleString(ref s) => Some(s),
       Frame::Error(ref s)        => Some(s),
       _                          => None
     }
   }
 
+  /// Attempt to read the frame value as an integer, parsing simple string and bulk string values as base-10 integers.
+  pub fn as_integer(&self) -> Option<i64> {
+    match *self {
+      Frame::Integer(i)          => Some(i),
+      Frame::SimpleString(ref s) => s.parse::<i64>().ok(),
+      Frame::BulkString(ref b)   => str::from_utf8(b).ok().and_then(|s| s.parse::<i64>().ok()),
+      _                          => None
+    }
+  }
+
+  /// Attempt to read the frame value as a double, converting integers and big numbers and parsing simple string and bulk
+  /// string values, such as the replies to `INCRBYFLOAT` or `ZSCORE`. Strings may also be `inf`, `-inf`, or `nan`.
+  pub fn as_f64(&self) -> Option<f64> {
+    match *self {
+      Frame::Double(d)           => Some(d),
+      Frame::Integer(i)          => Some(i as f64),
+      Frame::BigNumber(ref s)    => s.parse::<f64>().ok(),
+      Frame::SimpleString(ref s) => s.parse::<f64>().ok(),
+      Frame::BulkString(ref b)   => str::from_utf8(b).ok().and_then(|s| s.parse::<f64>().ok()),
+      _                          => None
+    }
+  }
+
+  /// Attempt to read the frame as an index reply from commands such as `LPOS` or `ZRANK`, which reply with `-1` or a null when
+  /// the element is not found.
+  ///
+  /// Non-negative integers return `Some(Some(index))`, `-1` and nulls return `Some(None)`, and any other frame returns `None`.
+  pub fn as_optional_index(&self) -> Option<Option<i64>> {
+    match *self {
+      Frame::Integer(i) if i >= 0 => Some(Some(i)),
+      Frame::Integer(-1)
+        | Frame::Null
+        | Frame::NullResp3        => Some(None),
+      _                           => None
+    }
+  }
+
   /// Whether or not the frame is a simple string or bulk string.
   pub fn is_string(&self) -> bool {
     match *self {
@@ -275,11 +876,12 @@ impl Frame {
     }
   }
 
-  /// Whether or not the frame is Null.
+  /// Whether or not the frame is Null, in either its RESP2 or RESP3 form. RESP2 null arrays are decoded as `Frame::Null`.
   pub fn is_null(&self) -> bool {
     match *self {
-      Frame::Null => true,
-      _           => false
+      Frame::Null
+        | Frame::NullResp3 => true,
+      _                    => false
     }
   }
 
@@ -291,6 +893,46 @@ impl Frame {
     }
   }
 
+  /// Whether or not the frame is a RESP3 push, which is sent out of band instead of in reply to a command.
+  pub fn is_push(&self) -> bool {
+    match *self {
+      Frame::Push(_) => true,
+      _              => false
+    }
+  }
+
+  /// Read the payload of a simple string status reply, such as `OK` or `OK 12 keys loaded`.
+  pub fn status(&self) -> Option<&str> {
+    match *self {
+      Frame::SimpleString(ref s) => Some(s),
+      _                          => None
+    }
+  }
+
+  /// Whether or not the frame is exactly the `OK` status reply.
+  pub fn is_ok(&self) -> bool {
+    self.status() == Some("OK")
+  }
+
+  /// Read the format of a verbatim string.
+  pub fn verbatim_format(&self) -> Option<VerbatimFormat> {
+    match *self {
+      Frame::VerbatimString { format, .. } => Some(format),
+      _                                    => None
+    }
+  }
+
+  /// Whether or not the frame is the `QUEUED` reply to a command sent inside a `MULTI` block.
+  ///
+  /// The reply to `EXEC` is an array of the command replies, or a null array if the transaction was aborted by `WATCH`.
+  /// Null arrays are decoded as `Frame::Null`, so aborted transactions should be matched with `is_null`.
+  pub fn is_queued(&self) -> bool {
+    match *self {
+      Frame::SimpleString(ref s) => s == "QUEUED",
+      _                          => false
+    }
+  }
+
   /// Whether or not the frame is an integer.
   pub fn is_integer(&self) -> bool {
     match *self {
@@ -316,6 +958,184 @@ impl Frame {
     }
   }
 
+  /// Read the keys from a command array according to `spec`, such as `KeySpec::new(1, -1, 2)` for `MSET`.
+  ///
+  /// Arguments that are not bulk strings or simple strings are skipped, and an empty vector is returned if the frame is not an array.
+  pub fn extract_keys(&self, spec: KeySpec) -> Vec<&[u8]> {
+    let args = match *self {
+      Frame::Array(ref args) => args,
+      _ => return Vec::new()
+    };
+
+    if spec.first_key == 0 || spec.step == 0 || spec.first_key >= args.len() {
+      return Vec::new();
+    }
+
+    let last_key = if spec.last_key < 0 {
+      args.len() as isize + spec.last_key
+    }else{
+      cmp::min(spec.last_key, args.len() as isize - 1)
+    };
+    if last_key < spec.first_key as isize {
+      return Vec::new();
+    }
+
+    (spec.first_key..(last_key as usize + 1)).step_by(spec.step).filter_map(|idx| match args[idx] {
+      Frame::BulkString(ref b)   => Some(&b[..]),
+      Frame::SimpleString(ref s) => Some(s.as_bytes()),
+      _                          => None
+    }).collect()
+  }
+
+  /// Recursively collect every bulk string payload in the frame in order, skipping any other leaf frames.
+  ///
+  /// Map and attribute frames are read as alternating keys and values.
+  pub fn flatten_bulk(&self) -> Vec<&[u8]> {
+    let mut out = Vec::new();
+    flatten_bulk_into(self, &mut out);
+    out
+  }
+
+  /// Replace every bulk string payload in the frame with the result of `f`, recursing into aggregate frames.
+  ///
+  /// Other frames are left unchanged.
+  pub fn map_bulk_strings<F: FnMut(&[u8]) -> Vec<u8>>(&mut self, mut f: F) {
+    map_bulk_strings_with(self, &mut f);
+  }
+
+  /// Shrink the capacity of every string, byte, and aggregate buffer in the frame to fit its contents, recursing into
+  /// aggregate frames. This is intended for frames that are held for a long time, such as in a cache.
+  pub fn shrink_to_fit(&mut self) {
+    match *self {
+      Frame::SimpleString(ref mut s)
+        | Frame::Error(ref mut s)
+        | Frame::Moved(ref mut s)
+        | Frame::Ask(ref mut s)
+        | Frame::BigNumber(ref mut s) => s.shrink_to_fit(),
+      Frame::BulkString(ref mut b)
+        | Frame::VerbatimString { data: ref mut b, .. } => b.shrink_to_fit(),
+      Frame::Array(ref mut frames)
+        | Frame::Set(ref mut frames)
+        | Frame::Push(ref mut frames) => {
+        frames.shrink_to_fit();
+        for frame in frames.iter_mut() {
+          frame.shrink_to_fit();
+        }
+      },
+      Frame::Map(ref mut pairs)
+        | Frame::Attribute(ref mut pairs) => {
+        pairs.shrink_to_fit();
+        for (key, value) in pairs.iter_mut() {
+          key.shrink_to_fit();
+          value.shrink_to_fit();
+        }
+      },
+      _ => {}
+    }
+  }
+
+  /// Convert any simple string arguments in a command array into bulk strings, so that handlers see every argument in the
+  /// same form. Some older clients and tools send arguments as simple strings, such as `*2\r\n$3\r\nGET\r\n+key\r\n`.
+  ///
+  /// Frames that are not arrays, and arguments of any other type, are left unchanged.
+  pub fn normalize_command_args(&mut self) {
+    if let Frame::Array(ref mut frames) = *self {
+      for frame in frames.iter_mut() {
+        let bytes = match *frame {
+          Frame::SimpleString(ref mut s) => mem::take(s).into_bytes(),
+          _ => continue
+        };
+
+        *frame = Frame::BulkString(bytes);
+      }
+    }
+  }
+
+  /// Check that all the keys read from a command array according to `spec` map to the same cluster key slot.
+  ///
+  /// Returns the shared slot, `None` if the command has no keys, or an error describing the conflicting slots.
+  pub fn same_slot(&self, spec: KeySpec) -> Result<Option<u16>, RedisProtocolError> {
+    let mut slot = None;
+
+    for key in self.extract_keys(spec).into_iter() {
+      let key_slot = utils::redis_keyslot_bytes(key);
+
+      match slot {
+        Some(slot) if slot != key_slot => {
+          return Err(RedisProtocolError::new(
+            RedisProtocolErrorKind::EncodeError,
+            format!("Keys map to different slots: {} and {}.", slot, key_slot)
+          ));
+        },
+        _ => slot = Some(key_slot)
+      };
+    }
+
+    Ok(slot)
+  }
+
+  /// Return a value that displays the frame for logging, rendering bulk strings that are not valid UTF-8 with replacement
+  /// characters instead of as byte arrays.
+  pub fn display_lossy<'a>(&'a self) -> impl fmt::Display + 'a {
+    LossyDisplay(self)
+  }
+
+  /// Check that the frame and any inner frames can be encoded into a valid message, returning an `EncodeError` describing
+  /// the first problem found.
+  ///
+  /// Simple strings and errors must not contain CR or LF, redirections must contain a slot and address, optionally preceded by
+  /// `MOVED` or `ASK` to match the frame kind, and big numbers must be base-10 digits.
+  pub fn validate(&self) -> Result<(), RedisProtocolError<'static>> {
+    let invalid = |desc: &'static str| Err(RedisProtocolError::new(RedisProtocolErrorKind::EncodeError, desc));
+
+    match *self {
+      Frame::SimpleString(ref s)
+        | Frame::Error(ref s) if s.contains(&['\r', '\n'][..]) => {
+        return invalid("Invalid CR or LF in simple string or error.");
+      },
+      Frame::Moved(ref s) | Frame::Ask(ref s) => {
+        let prefix = if self.kind() == FrameKind::Moved { "MOVED " }else{ "ASK " };
+        // the decoder strips the prefix, so accept redirections with or without it
+        let is_redirection = if s.starts_with(prefix) {
+          utils::string_to_redirection(s).is_ok()
+        }else{
+          utils::string_to_redirection(&format!("{}{}", prefix, s)).is_ok()
+        };
+
+        if s.contains(&['\r', '\n'][..]) || !is_redirection {
+          return invalid("Invalid redirection.");
+        }
+      },
+      Frame::BigNumber(ref s) => {
+        let digits = s.strip_prefix('-').unwrap_or(s);
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+          return invalid("Invalid big number.");
+        }
+      },
+      Frame::Array(ref frames)
+        | Frame::Set(ref frames)
+        | Frame::Push(ref frames) => for frame in frames.iter() {
+        frame.validate()?;
+      },
+      Frame::Map(ref pairs)
+        | Frame::Attribute(ref pairs) => for (key, value) in pairs.iter() {
+        key.validate()?;
+        value.validate()?;
+      },
+      _ => {}
+    };
+
+    Ok(())
+  }
+
+  /// Render the frame for debugging with one value per line, each prefixed by its frame kind. Elements of aggregate frames
+  /// are indented `indent` spaces deeper than the aggregate, and map values are indented beneath their keys.
+  pub fn pretty(&self, indent: usize) -> String {
+    let mut out = String::new();
+    pretty_into(self, indent, 0, &mut out);
+    out
+  }
+
   /// Attempt to parse the frame as a publish-subscribe message, returning the `(channel, message)` tuple
   /// if successful, or the original frame if the inner data is not a publish-subscribe message.
   pub fn parse_as_pubsub(self) -> Result<(String, String), Self> {
@@ -339,24 +1159,692 @@ impl Frame {
     }
   }
 
+  /// Attempt to read the frame as a keyspace notification, from either a `__keyspace@<db>__:<key>` or
+  /// `__keyevent@<db>__:<event>` channel.
+  pub fn as_keyspace_event(&self) -> Option<KeyspaceEvent> {
+    let (channel, payload) = match *self {
+      Frame::Array(ref frames) if utils::is_normal_pubsub(frames)  => (frames[1].as_str()?, frames[2].as_str()?),
+      Frame::Array(ref frames) if utils::is_pattern_pubsub(frames) => (frames[2].as_str()?, frames[3].as_str()?),
+      _ => return None
+    };
+
+    let (is_keyspace, db, suffix) = utils::read_keyspace_channel(channel)?;
+    let (key, event) = if is_keyspace {
+      (suffix, payload)
+    }else{
+      (payload, suffix)
+    };
+
+    Some(KeyspaceEvent {
+      db,
+      key: key.to_owned(),
+      event: event.to_owned()
+    })
+  }
+
   /// Attempt to parse the frame as a cluster redirection.
+  ///
+  /// With the `redirection-cache` feature the parsed redirection is read from a shared cache. See `RedirectionCache::shared`.
   pub fn to_redirection(&self) -> Result<Redirection, RedisProtocolError> {
     match *self {
-      Frame::Moved(ref s) => utils::string_to_redirection(s),
-      Frame::Ask(ref s)   => utils::string_to_redirection(s),
-      Frame::Error(ref s) => utils::string_to_redirection(s),
+      Frame::Moved(ref s) => utils::parse_redirection(s),
+      Frame::Ask(ref s)   => utils::parse_redirection(s),
+      Frame::Error(ref s) => utils::parse_redirection(s),
       _ => Err(RedisProtocolError::new(RedisProtocolErrorKind::Unknown, "Invalid frame kind. Expected Moved, Ask, or Error."))
     }
   }
 
-}
+  /// Attempt to read the frame as an error, splitting the payload into the error code and message on the first space.
+  ///
+  /// The code for `Moved` and `Ask` frames is `MOVED` or `ASK`, with the slot and address as the message whether or not the
+  /// payload starts with the code.
+  pub fn as_server_error(&self) -> Option<ServerError> {
+    let (code, message) = match *self {
+      Frame::Error(ref s) => match s.find(' ') {
+        Some(idx) => (&s[0..idx], &s[idx + 1..]),
+        None      => (&s[..], "")
+      },
+      Frame::Moved(ref s) => ("MOVED", s.strip_prefix("MOVED ").unwrap_or(s)),
+      Frame::Ask(ref s)   => ("ASK", s.strip_prefix("ASK ").unwrap_or(s)),
+      _ => return None
+    };
 
-impl From<Redirection> for Frame {
-  fn from(redirection: Redirection) -> Self {
-    match redirection {
-      Redirection::Moved {slot, host, port} => Frame::Moved(utils::redirection_to_frame("MOVED", slot, &host, port)),
-      Redirection::Ask {slot, host, port}   => Frame::Ask(utils::redirection_to_frame("ASK", slot, &host, port))
-    }
+    Some(ServerError {
+      code: code.to_owned(),
+      message: message.to_owned()
+    })
+  }
+
+  /// Copy the frame, truncating each simple string and bulk string to at most `max_bytes` bytes followed by a `...(+N bytes)`
+  /// marker describing how many bytes were removed. Aggregate frames are truncated recursively.
+  ///
+  /// This is intended for logging frames with large payloads, and the result should not be sent to a server.
+  pub fn truncate_for_logging(&self, max_bytes: usize) -> Frame {
+    match *self {
+      Frame::SimpleString(ref s) => Frame::SimpleString(utils::truncate_string(s, max_bytes)),
+      Frame::BulkString(ref b)   => Frame::BulkString(utils::truncate_bytes(b, max_bytes)),
+      Frame::Array(ref frames)   => Frame::Array(utils::truncate_frames(frames, max_bytes)),
+      Frame::Set(ref frames)     => Frame::Set(utils::truncate_frames(frames, max_bytes)),
+      Frame::Push(ref frames)    => Frame::Push(utils::truncate_frames(frames, max_bytes)),
+      Frame::Map(ref pairs)      => Frame::Map(utils::truncate_pairs(pairs, max_bytes)),
+      Frame::Attribute(ref p)    => Frame::Attribute(utils::truncate_pairs(p, max_bytes)),
+      _                          => self.clone()
+    }
+  }
+
+  /// Create an error frame from an error code such as `WRONGTYPE` and a message, separated by a space. The frame only contains
+  /// the code if `message` is empty.
+  ///
+  /// Returns an error if `code` is empty or contains anything other than uppercase ASCII letters, or if `message` contains a CR or LF.
+  pub fn typed_error(code: &str, message: &str) -> Result<Frame, RedisProtocolError<'static>> {
+    if code.is_empty() || !code.bytes().all(|b| b.is_ascii_uppercase()) {
+      return Err(RedisProtocolError::new(RedisProtocolErrorKind::EncodeError, "Invalid error code."));
+    }
+    if message.bytes().any(|b| b == b'\r' || b == b'\n') {
+      return Err(RedisProtocolError::new(RedisProtocolErrorKind::EncodeError, "Invalid error message."));
+    }
+
+    if message.is_empty() {
+      Ok(Frame::Error(code.to_owned()))
+    }else{
+      Ok(Frame::Error(format!("{} {}", code, message)))
+    }
+  }
+
+  /// Wrap `commands` in a transaction, returning a `MULTI` command followed by the commands and a trailing `EXEC` command.
+  pub fn transaction(commands: Vec<Frame>) -> Vec<Frame> {
+    let mut frames = Vec::with_capacity(commands.len() + 2);
+    frames.push(Frame::Array(vec![Frame::BulkString("MULTI".into())]));
+    frames.extend(commands);
+    frames.push(Frame::Array(vec![Frame::BulkString("EXEC".into())]));
+
+    frames
+  }
+
+  /// Create a `HELLO` command to negotiate the protocol version `protover`, with optional `AUTH` username and password
+  /// and `SETNAME` client name arguments.
+  pub fn hello(protover: u8, auth: Option<(&str, &str)>, client_name: Option<&str>) -> Frame {
+    let mut args = vec![
+      Frame::BulkString("HELLO".into()),
+      Frame::BulkString(protover.to_string().into())
+    ];
+
+    if let Some((username, password)) = auth {
+      args.push(Frame::BulkString("AUTH".into()));
+      args.push(Frame::BulkString(username.into()));
+      args.push(Frame::BulkString(password.into()));
+    }
+    if let Some(name) = client_name {
+      args.push(Frame::BulkString("SETNAME".into()));
+      args.push(Frame::BulkString(name.into()));
+    }
+
+    Frame::Array(args)
+  }
+
+  /// Create a `BulkString` frame from `data`, returning an `EncodeError` if its length is not `declared_len`.
+  pub fn bulk_string_checked(declared_len: usize, data: Vec<u8>) -> Result<Frame, RedisProtocolError<'static>> {
+    if data.len() == declared_len {
+      Ok(Frame::BulkString(data))
+    }else{
+      Err(RedisProtocolError::new(
+        RedisProtocolErrorKind::EncodeError,
+        format!("Expected bulk string with {} bytes, found {}.", declared_len, data.len())
+      ))
+    }
+  }
+
+  /// Create an `Integer` frame if `value` fits in an `i64`, otherwise a RESP3 `BigNumber` frame.
+  pub fn integer_or_bignumber(value: i128) -> Frame {
+    if value >= i64::MIN as i128 && value <= i64::MAX as i128 {
+      Frame::Integer(value as i64)
+    }else{
+      Frame::BigNumber(value.to_string())
+    }
+  }
+
+  /// Attempt to encode the frame into a new `Bytes` buffer. See `encode::encode_to_bytes` for more information.
+  pub fn encode_to_bytes(&self) -> Result<Bytes, RedisProtocolError> {
+    encode::encode_to_bytes(self)
+  }
+
+  /// Read the number of bytes the frame uses on the wire, which is the number of bytes `encode` will write for the frame.
+  pub fn byte_len(&self) -> usize {
+    utils::encode_len(self)
+  }
+
+  /// Convert the frame to a JSON value, intended for logging or exposing replies over an API.
+  ///
+  /// Integers, doubles, and booleans are rendered as JSON numbers and booleans, simple and bulk strings as JSON strings,
+  /// arrays, sets, and pushes as JSON arrays, maps and attributes as JSON objects, nulls as `null`, and errors as
+  /// `{"error": "..."}`. Bulk strings that are not valid UTF-8 are rendered as `{"string": "...", "lossy": true}` with invalid
+  /// sequences replaced. Doubles that cannot be represented as JSON numbers are rendered as strings. Map keys that are not
+  /// rendered as JSON strings use their JSON text as the key.
+  #[cfg(feature = "json")]
+  pub fn to_json_value(&self) -> serde_json::Value {
+    use serde_json::{Map, Number, Value};
+
+    match *self {
+      Frame::SimpleString(ref s) => Value::String(s.clone()),
+      Frame::BulkString(ref b)   => match str::from_utf8(b) {
+        Ok(s)  => Value::String(s.to_owned()),
+        Err(_) => {
+          let mut map = Map::new();
+          map.insert("string".into(), Value::String(String::from_utf8_lossy(b).into_owned()));
+          map.insert("lossy".into(), Value::Bool(true));
+          Value::Object(map)
+        }
+      },
+      Frame::Integer(i)          => Value::Number(i.into()),
+      Frame::Double(d)           => match Number::from_f64(d) {
+        Some(n) => Value::Number(n),
+        None    => Value::String(utils::double_to_string(d))
+      },
+      Frame::Boolean(b)          => Value::Bool(b),
+      Frame::BigNumber(ref s)    => Value::String(s.clone()),
+      Frame::VerbatimString { ref data, .. } => Value::String(String::from_utf8_lossy(data).into_owned()),
+      Frame::Array(ref frames)
+        | Frame::Set(ref frames)
+        | Frame::Push(ref frames) => Value::Array(frames.iter().map(|f| f.to_json_value()).collect()),
+      Frame::Map(ref pairs)
+        | Frame::Attribute(ref pairs) => {
+        let mut map = Map::new();
+        for (key, value) in pairs.iter() {
+          let key = match key.to_json_value() {
+            Value::String(s) => s,
+            key              => key.to_string()
+          };
+          map.insert(key, value.to_json_value());
+        }
+        Value::Object(map)
+      },
+      Frame::Null
+        | Frame::NullResp3       => Value::Null,
+      Frame::Error(ref s)
+        | Frame::Moved(ref s)
+        | Frame::Ask(ref s)      => {
+        let mut map = Map::new();
+        map.insert("error".into(), Value::String(s.clone()));
+        Value::Object(map)
+      }
+    }
+  }
+
+  /// Append `arg` to a command array as a bulk string, returning an error if the frame is not an array.
+  pub fn append_arg<B: Into<Vec<u8>>>(&mut self, arg: B) -> Result<(), RedisProtocolError<'static>> {
+    match *self {
+      Frame::Array(ref mut frames) => {
+        frames.push(Frame::BulkString(arg.into()));
+        Ok(())
+      },
+      _ => Err(RedisProtocolError::new(RedisProtocolErrorKind::EncodeError, "Expected array frame."))
+    }
+  }
+
+  /// Convert the command name at the start of a command array to ASCII uppercase in place, leaving the arguments unchanged.
+  ///
+  /// This is a no-op for frames that are not arrays or that do not start with a bulk string or simple string.
+  pub fn uppercase_command(&mut self) {
+    if let Frame::Array(ref mut frames) = *self {
+      match frames.first_mut() {
+        Some(&mut Frame::BulkString(ref mut b))   => b.make_ascii_uppercase(),
+        Some(&mut Frame::SimpleString(ref mut s)) => s.make_ascii_uppercase(),
+        _ => {}
+      };
+    }
+  }
+
+  /// Read the number of arguments in a command array, not including the command name.
+  pub fn command_arg_count(&self) -> Option<usize> {
+    match *self {
+      Frame::Array(ref frames) if !frames.is_empty() => Some(frames.len() - 1),
+      _ => None
+    }
+  }
+
+  /// Check that a command array has at least `min` and at most `max` arguments, not including the command name.
+  pub fn check_arity(&self, min: usize, max: Option<usize>) -> Result<(), RedisProtocolError<'static>> {
+    let count = match self.command_arg_count() {
+      Some(count) => count,
+      None => return Err(RedisProtocolError::new(RedisProtocolErrorKind::EncodeError, "Expected command array."))
+    };
+
+    if count < min || max.map(|max| count > max).unwrap_or(false) {
+      let name = match *self {
+        Frame::Array(ref frames) => frames[0].as_str().unwrap_or("").to_lowercase(),
+        _ => String::new()
+      };
+
+      Err(RedisProtocolError::new(RedisProtocolErrorKind::EncodeError, format!("Wrong number of arguments for '{}' command.", name)))
+    }else{
+      Ok(())
+    }
+  }
+
+  /// Read the first argument of a command array, which is the key of most single key commands.
+  ///
+  /// Returns `None` if the frame is not an array, has fewer than two elements, or the first argument is not a bulk string
+  /// or simple string.
+  pub fn first_key(&self) -> Option<&[u8]> {
+    let frames = match *self {
+      Frame::Array(ref frames) if frames.len() > 1 => frames,
+      _ => return None
+    };
+
+    match frames[1] {
+      Frame::BulkString(ref b)   => Some(&b[..]),
+      Frame::SimpleString(ref s) => Some(s.as_bytes()),
+      _                          => None
+    }
+  }
+
+  /// Parse the element at `index` of a command array as a base-10 integer, where the command name is at index 0.
+  ///
+  /// Returns an error if the frame is not an array, `index` is out of range, or the element is not a string containing an integer.
+  pub fn arg_as_integer(&self, index: usize) -> Result<i64, RedisProtocolError<'static>> {
+    let frames = match *self {
+      Frame::Array(ref frames) => frames,
+      _ => return Err(RedisProtocolError::new(RedisProtocolErrorKind::Unknown, "Expected command array."))
+    };
+
+    let arg = match frames.get(index) {
+      Some(Frame::BulkString(b))   => &b[..],
+      Some(Frame::SimpleString(s)) => s.as_bytes(),
+      Some(_)                      => return Err(RedisProtocolError::new(RedisProtocolErrorKind::Unknown, format!("Expected string argument at index {}.", index))),
+      None                         => return Err(RedisProtocolError::new(RedisProtocolErrorKind::Unknown, format!("Missing argument at index {}.", index)))
+    };
+
+    str::from_utf8(arg).ok()
+      .and_then(|s| s.parse::<i64>().ok())
+      .ok_or_else(|| RedisProtocolError::new(RedisProtocolErrorKind::Unknown, format!("Invalid integer argument at index {}.", index)))
+  }
+
+  /// Merge several array frames into one array containing the elements of each array in order.
+  ///
+  /// Returns an error if any of the frames is not an array.
+  pub fn concat_arrays(frames: Vec<Frame>) -> Result<Frame, RedisProtocolError<'static>> {
+    let mut out = Vec::new();
+
+    for frame in frames.into_iter() {
+      match frame {
+        Frame::Array(inner) => out.extend(inner),
+        _ => return Err(RedisProtocolError::new(RedisProtocolErrorKind::Unknown, format!("Expected array frame, found {:?}.", frame.kind())))
+      }
+    }
+
+    Ok(Frame::Array(out))
+  }
+
+  /// Convert the frame into an array, wrapping any frame that is not an array, including nulls, in a one element array.
+  pub fn into_array(self) -> Frame {
+    match self {
+      Frame::Array(frames) => Frame::Array(frames),
+      frame                => Frame::Array(vec![frame])
+    }
+  }
+
+  /// Unwrap a one element array into its only element. Any other frame is returned unchanged.
+  pub fn unwrap_single(self) -> Frame {
+    match self {
+      Frame::Array(mut frames) => if frames.len() == 1 {
+        frames.pop().unwrap()
+      }else{
+        Frame::Array(frames)
+      },
+      frame => frame
+    }
+  }
+
+  /// Retain only the elements of an array for which `f` returns `true`. This is a no-op for frames that are not arrays.
+  pub fn retain_array<F: FnMut(&Frame) -> bool>(&mut self, f: F) {
+    if let Frame::Array(ref mut frames) = *self {
+      frames.retain(f);
+    }
+  }
+
+  /// Compute a 64 bit FNV-1a digest of the frame's wire encoding.
+  ///
+  /// Unlike the standard library hasher the result is stable across processes, so it can be used as a cache or deduplication key.
+  /// The encoded bytes are hashed as they are produced, so the encoding is never held in memory.
+  pub fn checksum(&self) -> u64 {
+    let mut hasher = utils::Fnv64::new();
+    utils::hash_frame(&mut hasher, self);
+    hasher.finish()
+  }
+
+  /// Stably sort the elements of an array by their encoded byte representation, so that two arrays with the same elements in
+  /// a different order compare equal. Nested arrays are compared by their full encoding but are not sorted themselves.
+  ///
+  /// Elements that cannot be encoded are sorted before all other elements. This is a no-op for frames that are not arrays.
+  pub fn sort_array(&mut self) {
+    if let Frame::Array(ref mut frames) = *self {
+      frames.sort_by_cached_key(|f| encode::encode_to_bytes(f).ok());
+    }
+  }
+
+}
+
+struct LossyDisplay<'a>(&'a Frame);
+
+impl<'a> LossyDisplay<'a> {
+
+  fn fmt_frames(f: &mut fmt::Formatter, frames: &[Frame]) -> fmt::Result {
+    write!(f, "[")?;
+    for (idx, frame) in frames.iter().enumerate() {
+      if idx > 0 {
+        write!(f, ", ")?;
+      }
+      write!(f, "{}", LossyDisplay(frame))?;
+    }
+    write!(f, "]")
+  }
+
+  fn fmt_pairs(f: &mut fmt::Formatter, pairs: &[(Frame, Frame)]) -> fmt::Result {
+    write!(f, "{{")?;
+    for (idx, (key, value)) in pairs.iter().enumerate() {
+      if idx > 0 {
+        write!(f, ", ")?;
+      }
+      write!(f, "{}: {}", LossyDisplay(key), LossyDisplay(value))?;
+    }
+    write!(f, "}}")
+  }
+
+}
+
+impl<'a> fmt::Display for LossyDisplay<'a> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match *self.0 {
+      Frame::SimpleString(ref s)
+        | Frame::Error(ref s)
+        | Frame::Moved(ref s)
+        | Frame::Ask(ref s)       => write!(f, "{}", s),
+      Frame::BulkString(ref b)    => write!(f, "{}", String::from_utf8_lossy(b)),
+      Frame::Integer(ref i)       => write!(f, "{}", i),
+      Frame::Double(ref d)        => write!(f, "{}", utils::double_to_string(*d)),
+      Frame::Boolean(ref b)       => write!(f, "{}", b),
+      Frame::BigNumber(ref s)     => write!(f, "{}", s),
+      Frame::VerbatimString { ref data, .. } => write!(f, "{}", String::from_utf8_lossy(data)),
+      Frame::Null
+        | Frame::NullResp3        => write!(f, "nil"),
+      Frame::Array(ref frames)
+        | Frame::Set(ref frames)
+        | Frame::Push(ref frames) => LossyDisplay::fmt_frames(f, frames),
+      Frame::Map(ref pairs)
+        | Frame::Attribute(ref pairs) => LossyDisplay::fmt_pairs(f, pairs)
+    }
+  }
+}
+
+/// A borrowed view of a frame, where string-like payloads reference slices of the decoded buffer instead of owned allocations.
+///
+/// Payloads are not checked for UTF-8 validity until the view is converted to a `Frame`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FrameView<'a> {
+  SimpleString(&'a [u8]),
+  Error(&'a [u8]),
+  Integer(i64),
+  BulkString(&'a [u8]),
+  Array(Vec<FrameView<'a>>),
+  Null,
+  NullResp3,
+  Double(f64),
+  Boolean(bool),
+  BigNumber(&'a [u8]),
+  VerbatimString {
+    format: VerbatimFormat,
+    data: &'a [u8]
+  },
+  Map(Vec<(FrameView<'a>, FrameView<'a>)>),
+  Set(Vec<FrameView<'a>>),
+  Push(Vec<FrameView<'a>>),
+  Attribute(Vec<(FrameView<'a>, FrameView<'a>)>)
+}
+
+impl<'a> FrameView<'a> {
+
+  /// Read the `FrameKind` value for this frame view.
+  pub fn kind(&self) -> FrameKind {
+    match *self {
+      FrameView::SimpleString(_) => FrameKind::SimpleString,
+      FrameView::Error(_)        => FrameKind::Error,
+      FrameView::Integer(_)      => FrameKind::Integer,
+      FrameView::BulkString(_)   => FrameKind::BulkString,
+      FrameView::Array(_)        => FrameKind::Array,
+      FrameView::Null            => FrameKind::Null,
+      FrameView::NullResp3       => FrameKind::NullResp3,
+      FrameView::Double(_)       => FrameKind::Double,
+      FrameView::Boolean(_)      => FrameKind::Boolean,
+      FrameView::BigNumber(_)    => FrameKind::BigNumber,
+      FrameView::VerbatimString { .. } => FrameKind::VerbatimString,
+      FrameView::Map(_)          => FrameKind::Map,
+      FrameView::Set(_)          => FrameKind::Set,
+      FrameView::Push(_)         => FrameKind::Push,
+      FrameView::Attribute(_)    => FrameKind::Attribute
+    }
+  }
+
+  /// Read the simple string, error, bulk string, or verbatim string payload as a string borrowed from the input buffer.
+  ///
+  /// Returns `None` if the view does not have a string payload or the payload is not valid UTF-8, matching `to_owned`.
+  pub fn as_str(&self) -> Option<Cow<'a, str>> {
+    let payload = match *self {
+      FrameView::SimpleString(s) => s,
+      FrameView::Error(s)        => s,
+      FrameView::BulkString(b)   => b,
+      FrameView::VerbatimString { data, .. } => data,
+      _                          => return None
+    };
+
+    str::from_utf8(payload).ok().map(Cow::Borrowed)
+  }
+
+  /// Slice the string payload of the view out of `src` without copying, where the view was decoded from `src`.
+  ///
+  /// Returns `None` if the view does not have a string payload or the payload is not part of `src`. Note that `Bytes`
+  /// stores small slices inline, so short payloads are copied rather than sharing the allocation of `src`.
+  pub fn payload_bytes(&self, src: &Bytes) -> Option<Bytes> {
+    let payload = match *self {
+      FrameView::SimpleString(s)
+        | FrameView::Error(s)
+        | FrameView::BulkString(s)
+        | FrameView::BigNumber(s)
+        | FrameView::VerbatimString { data: s, .. } => s,
+      _ => return None
+    };
+
+    let src_start = src.as_ptr() as usize;
+    let start = payload.as_ptr() as usize;
+
+    if start >= src_start && start + payload.len() <= src_start + src.len() {
+      let offset = start - src_start;
+      Some(src.slice(offset, offset + payload.len()))
+    }else{
+      None
+    }
+  }
+
+  /// Copy the view into an owned `Frame`, mapping cluster redirection errors to `Moved` or `Ask` frames.
+  ///
+  /// Returns an error if a simple string or error payload is not valid UTF-8.
+  pub fn to_owned(&self) -> Result<Frame, RedisProtocolError<'a>> {
+    let frame = match *self {
+      FrameView::SimpleString(s) => Frame::SimpleString(utils::view_to_string(s)?),
+      FrameView::Error(s)        => {
+        let s = utils::view_to_string(s)?;
+        utils::read_cluster_error(&s).unwrap_or(Frame::Error(s))
+      },
+      FrameView::Integer(i)      => Frame::Integer(i),
+      FrameView::BulkString(b)   => Frame::BulkString(b.to_vec()),
+      FrameView::Array(ref v)     => Frame::Array(views_to_owned(v)?),
+      FrameView::Set(ref v)       => Frame::Set(views_to_owned(v)?),
+      FrameView::Push(ref v)      => Frame::Push(views_to_owned(v)?),
+      FrameView::Map(ref v)       => Frame::Map(view_pairs_to_owned(v)?),
+      FrameView::Attribute(ref v) => Frame::Attribute(view_pairs_to_owned(v)?),
+      FrameView::Null            => Frame::Null,
+      FrameView::NullResp3       => Frame::NullResp3,
+      FrameView::Double(d)       => Frame::Double(d),
+      FrameView::Boolean(b)      => Frame::Boolean(b),
+      FrameView::BigNumber(s)    => Frame::BigNumber(utils::view_to_string(s)?),
+      FrameView::VerbatimString { format, data } => Frame::VerbatimString { format, data: data.to_vec() }
+    };
+
+    Ok(frame)
+  }
+
+}
+
+fn pretty_into(frame: &Frame, indent: usize, depth: usize, out: &mut String) {
+  out.push_str(&" ".repeat(indent * depth));
+
+  match *frame {
+    Frame::Array(ref frames)
+      | Frame::Set(ref frames)
+      | Frame::Push(ref frames) => {
+      out.push_str(&format!("{:?}({})\n", frame.kind(), frames.len()));
+      for frame in frames.iter() {
+        pretty_into(frame, indent, depth + 1, out);
+      }
+    },
+    Frame::Map(ref pairs)
+      | Frame::Attribute(ref pairs) => {
+      out.push_str(&format!("{:?}({})\n", frame.kind(), pairs.len()));
+      for (key, value) in pairs.iter() {
+        pretty_into(key, indent, depth + 1, out);
+        pretty_into(value, indent, depth + 2, out);
+      }
+    },
+    Frame::SimpleString(ref s)
+      | Frame::Error(ref s)
+      | Frame::Moved(ref s)
+      | Frame::Ask(ref s)      => out.push_str(&format!("{:?} {:?}\n", frame.kind(), s)),
+    Frame::BulkString(ref b)
+      | Frame::VerbatimString { data: ref b, .. } => out.push_str(&format!("{:?} {:?}\n", frame.kind(), String::from_utf8_lossy(b))),
+    Frame::Null
+      | Frame::NullResp3       => out.push_str(&format!("{:?}\n", frame.kind())),
+    _                          => out.push_str(&format!("{:?} {}\n", frame.kind(), frame.display_lossy()))
+  }
+}
+
+fn map_bulk_strings_with<F: FnMut(&[u8]) -> Vec<u8>>(frame: &mut Frame, f: &mut F) {
+  match *frame {
+    Frame::BulkString(ref mut b) => *b = f(b),
+    Frame::Array(ref mut frames)
+      | Frame::Set(ref mut frames)
+      | Frame::Push(ref mut frames) => {
+      for frame in frames.iter_mut() {
+        map_bulk_strings_with(frame, f);
+      }
+    },
+    Frame::Map(ref mut pairs)
+      | Frame::Attribute(ref mut pairs) => {
+      for (key, value) in pairs.iter_mut() {
+        map_bulk_strings_with(key, f);
+        map_bulk_strings_with(value, f);
+      }
+    },
+    _ => {}
+  }
+}
+
+fn flatten_bulk_into<'a>(frame: &'a Frame, out: &mut Vec<&'a [u8]>) {
+  match *frame {
+    Frame::BulkString(ref b) => out.push(&b[..]),
+    Frame::Array(ref frames)
+      | Frame::Set(ref frames)
+      | Frame::Push(ref frames) => {
+      for frame in frames.iter() {
+        flatten_bulk_into(frame, out);
+      }
+    },
+    Frame::Map(ref pairs)
+      | Frame::Attribute(ref pairs) => {
+      for (key, value) in pairs.iter() {
+        flatten_bulk_into(key, out);
+        flatten_bulk_into(value, out);
+      }
+    },
+    _ => {}
+  }
+}
+
+fn views_to_owned<'a>(views: &[FrameView<'a>]) -> Result<Vec<Frame>, RedisProtocolError<'a>> {
+  let mut frames = Vec::with_capacity(views.len());
+  for view in views.iter() {
+    frames.push(view.to_owned()?);
+  }
+
+  Ok(frames)
+}
+
+fn view_pairs_to_owned<'a>(views: &[(FrameView<'a>, FrameView<'a>)]) -> Result<Vec<(Frame, Frame)>, RedisProtocolError<'a>> {
+  let mut pairs = Vec::with_capacity(views.len());
+  for (key, value) in views.iter() {
+    pairs.push((key.to_owned()?, value.to_owned()?));
+  }
+
+  Ok(pairs)
+}
+
+// NaN is equal to itself and greater than any other double, and negative zero is less than positive zero since they're
+// encoded differently
+fn cmp_double(lhs: f64, rhs: f64) -> cmp::Ordering {
+  let canonical = |d: f64| if d.is_nan() { f64::NAN }else{ d };
+  canonical(lhs).total_cmp(&canonical(rhs))
+}
+
+impl Ord for Frame {
+  fn cmp(&self, other: &Frame) -> cmp::Ordering {
+    match (self, other) {
+      (Frame::SimpleString(lhs), Frame::SimpleString(rhs))
+        | (Frame::Error(lhs), Frame::Error(rhs))
+        | (Frame::Moved(lhs), Frame::Moved(rhs))
+        | (Frame::Ask(lhs), Frame::Ask(rhs))
+        | (Frame::BigNumber(lhs), Frame::BigNumber(rhs))   => lhs.cmp(rhs),
+      (Frame::Integer(lhs), Frame::Integer(rhs))           => lhs.cmp(rhs),
+      (Frame::BulkString(lhs), Frame::BulkString(rhs))     => lhs.cmp(rhs),
+      (Frame::Double(lhs), Frame::Double(rhs))             => cmp_double(*lhs, *rhs),
+      (Frame::Boolean(lhs), Frame::Boolean(rhs))           => lhs.cmp(rhs),
+      (Frame::VerbatimString { format: lhs_format, data: lhs_data },
+        Frame::VerbatimString { format: rhs_format, data: rhs_data }) => {
+        lhs_format.cmp(rhs_format).then_with(|| lhs_data.cmp(rhs_data))
+      },
+      (Frame::Array(lhs), Frame::Array(rhs))
+        | (Frame::Set(lhs), Frame::Set(rhs))
+        | (Frame::Push(lhs), Frame::Push(rhs))             => lhs.cmp(rhs),
+      (Frame::Map(lhs), Frame::Map(rhs))
+        | (Frame::Attribute(lhs), Frame::Attribute(rhs))   => lhs.cmp(rhs),
+      _                                                    => self.kind().cmp(&other.kind())
+    }
+  }
+}
+
+impl PartialOrd for Frame {
+  fn partial_cmp(&self, other: &Frame) -> Option<cmp::Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl PartialEq for Frame {
+  fn eq(&self, other: &Frame) -> bool {
+    self.cmp(other) == cmp::Ordering::Equal
+  }
+}
+
+impl Eq for Frame {}
+
+fn canonical_pairs(pairs: &[(Frame, Frame)]) -> Vec<(Frame, Frame)> {
+  let mut pairs: Vec<(Frame, Frame)> = pairs.iter().map(|(k, v)| (k.canonical(), v.canonical())).collect();
+  pairs.sort();
+  pairs
+}
+
+impl From<Redirection> for Frame {
+  fn from(redirection: Redirection) -> Self {
+    match redirection {
+      Redirection::Moved {slot, host, port} => Frame::Moved(utils::redirection_to_frame("MOVED", slot, &host, port)),
+      Redirection::Ask {slot, host, port}   => Frame::Ask(utils::redirection_to_frame("ASK", slot, &host, port))
+    }
   }
 }
 
@@ -375,6 +1863,8 @@ mod tests {
   use super::*;
   use ::utils::ZEROED_KB;
 
+  use std::collections::BTreeSet;
+
   use nom::ErrorKind as NomErrorKind;
 
   #[test]
@@ -425,6 +1915,49 @@ mod tests {
     assert_eq!(frame.to_redirection().unwrap(), redirection);
   }
 
+  #[test]
+  fn should_convert_ipv4_redirection_to_socket_addr() {
+    let redirection = Redirection::Moved {
+      slot: 3999,
+      host: "127.0.0.1".into(),
+      port: 6381
+    };
+    let expected: SocketAddr = "127.0.0.1:6381".parse().unwrap();
+
+    assert_eq!(redirection.socket_addr().unwrap(), expected);
+  }
+
+  #[test]
+  fn should_convert_ipv6_redirection_to_socket_addr() {
+    let expected: SocketAddr = "[::1]:6381".parse().unwrap();
+
+    let redirection = Redirection::Ask {
+      slot: 3999,
+      host: "[::1]".into(),
+      port: 6381
+    };
+    assert_eq!(redirection.socket_addr().unwrap(), expected);
+
+    let redirection = Redirection::Ask {
+      slot: 3999,
+      host: "::1".into(),
+      port: 6381
+    };
+    assert_eq!(redirection.socket_addr().unwrap(), expected);
+  }
+
+  #[test]
+  #[should_panic]
+  fn should_convert_invalid_redirection_to_socket_addr_error() {
+    let redirection = Redirection::Moved {
+      slot: 3999,
+      host: "foo.bar".into(),
+      port: 6381
+    };
+
+    let _ = redirection.socket_addr().unwrap();
+  }
+
   #[test]
   #[should_panic]
   fn should_convert_frame_to_redirection_error() {
@@ -506,6 +2039,19 @@ mod tests {
     assert_eq!(message, "bar");
   }
 
+  #[test]
+  fn should_detect_subscription_confirmation() {
+    let frame = Frame::Array(vec![Frame::BulkString("subscribe".into()), Frame::BulkString("foo".into()), Frame::Integer(1)]);
+    assert!(frame.is_subscription_confirmation());
+
+    let frame = Frame::Push(vec![Frame::BulkString("punsubscribe".into()), Frame::Null, Frame::Integer(0)]);
+    assert!(frame.is_subscription_confirmation());
+
+    assert!(!command(&["message", "foo", "bar"]).is_subscription_confirmation());
+    assert!(!command(&["subscribe", "foo"]).is_subscription_confirmation());
+    assert!(!Frame::Integer(1).is_subscription_confirmation());
+  }
+
   #[test]
   #[should_panic]
   fn should_fail_parsing_non_pubsub_message() {
@@ -517,45 +2063,128 @@ mod tests {
     frame.parse_as_pubsub().expect("Expected non pubsub frames");
   }
 
-  // gotta pad those coveralls stats...
   #[test]
-  fn should_create_empty_error() {
-    let e = RedisProtocolError::new_empty();
-    let s = e.to_string();
+  fn should_read_server_error() {
+    let frame = Frame::Error("WRONGTYPE Operation against a key holding the wrong kind of value".into());
+    let expected = ServerError {
+      code: "WRONGTYPE".into(),
+      message: "Operation against a key holding the wrong kind of value".into()
+    };
 
-    assert_eq!(e.description(), "");
-    assert_eq!(e.kind(), &RedisProtocolErrorKind::Unknown);
-    assert_eq!(e.context(), None);
+    assert_eq!(frame.as_server_error(), Some(expected));
   }
 
   #[test]
-  fn should_create_encode_error() {
-    let e = RedisProtocolError::new(RedisProtocolErrorKind::EncodeError, "foo");
-    let s = e.to_string();
+  fn should_read_single_word_server_error() {
+    let frame = Frame::Error("NOAUTH".into());
+    let expected = ServerError { code: "NOAUTH".into(), message: "".into() };
 
-    assert_eq!(e.description(), "foo");
-    assert_eq!(e.kind(), &RedisProtocolErrorKind::EncodeError);
-    assert_eq!(e.context(), None);
+    assert_eq!(frame.as_server_error(), Some(expected));
+    assert_eq!(Frame::SimpleString("OK".into()).as_server_error(), None);
   }
 
   #[test]
-  fn should_create_decode_error() {
-    let e = RedisProtocolError::new(RedisProtocolErrorKind::DecodeError, "foo");
-    let s = e.to_string();
+  fn should_read_redirection_as_server_error() {
+    let frame = Frame::Moved("3999 127.0.0.1:6381".into());
+    let expected = ServerError { code: "MOVED".into(), message: "3999 127.0.0.1:6381".into() };
+    assert_eq!(frame.as_server_error(), Some(expected));
 
-    assert_eq!(e.description(), "foo");
-    assert_eq!(e.kind(), &RedisProtocolErrorKind::DecodeError);
-    assert_eq!(e.context(), None);
+    let frame = Frame::Ask("3999 127.0.0.1:6381".into());
+    assert_eq!(frame.as_server_error().unwrap().code, "ASK");
   }
 
   #[test]
-  fn should_create_buf_too_small_error() {
-    let e = RedisProtocolError::new(RedisProtocolErrorKind::BufferTooSmall(10), "foo");
-    let s = e.to_string();
+  fn should_read_prefixed_redirection_as_server_error() {
+    let frame = Frame::Moved("MOVED 3999 127.0.0.1:6381".into());
+    let expected = ServerError { code: "MOVED".into(), message: "3999 127.0.0.1:6381".into() };
+    assert_eq!(frame.as_server_error(), Some(expected));
 
-    assert_eq!(e.description(), "foo");
-    assert_eq!(e.kind(), &RedisProtocolErrorKind::BufferTooSmall(10));
-    assert_eq!(e.context(), None);
+    let frame: Frame = Redirection::Ask { slot: 3999, host: "127.0.0.1".into(), port: 6381 }.into();
+    let expected = ServerError { code: "ASK".into(), message: "3999 127.0.0.1:6381".into() };
+    assert_eq!(frame.as_server_error(), Some(expected));
+  }
+
+  #[test]
+  fn should_read_keyspace_event() {
+    let frame = Frame::Array(vec![
+      Frame::BulkString("message".into()),
+      Frame::BulkString("__keyspace@0__:foo".into()),
+      Frame::BulkString("set".into())
+    ]);
+    let expected = KeyspaceEvent { db: 0, key: "foo".into(), event: "set".into() };
+
+    assert_eq!(frame.as_keyspace_event(), Some(expected));
+  }
+
+  #[test]
+  fn should_read_pattern_keyevent_event() {
+    let frame = Frame::Array(vec![
+      Frame::BulkString("pmessage".into()),
+      Frame::BulkString("__keyevent@*__:*".into()),
+      Frame::BulkString("__keyevent@12__:expired".into()),
+      Frame::BulkString("foo".into())
+    ]);
+    let expected = KeyspaceEvent { db: 12, key: "foo".into(), event: "expired".into() };
+
+    assert_eq!(frame.as_keyspace_event(), Some(expected));
+  }
+
+  #[test]
+  fn should_not_read_keyspace_event_from_ordinary_message() {
+    let frame = Frame::Array(vec![
+      Frame::BulkString("message".into()),
+      Frame::BulkString("foo".into()),
+      Frame::BulkString("bar".into())
+    ]);
+    assert_eq!(frame.as_keyspace_event(), None);
+
+    let frame = Frame::Array(vec![
+      Frame::BulkString("message".into()),
+      Frame::BulkString("__keyspace@x__:foo".into()),
+      Frame::BulkString("set".into())
+    ]);
+    assert_eq!(frame.as_keyspace_event(), None);
+  }
+
+  // gotta pad those coveralls stats...
+  #[test]
+  fn should_create_empty_error() {
+    let e = RedisProtocolError::new_empty();
+    let s = e.to_string();
+
+    assert_eq!(e.description(), "");
+    assert_eq!(e.kind(), &RedisProtocolErrorKind::Unknown);
+    assert_eq!(e.context(), None);
+  }
+
+  #[test]
+  fn should_create_encode_error() {
+    let e = RedisProtocolError::new(RedisProtocolErrorKind::EncodeError, "foo");
+    let s = e.to_string();
+
+    assert_eq!(e.description(), "foo");
+    assert_eq!(e.kind(), &RedisProtocolErrorKind::EncodeError);
+    assert_eq!(e.context(), None);
+  }
+
+  #[test]
+  fn should_create_decode_error() {
+    let e = RedisProtocolError::new(RedisProtocolErrorKind::DecodeError, "foo");
+    let s = e.to_string();
+
+    assert_eq!(e.description(), "foo");
+    assert_eq!(e.kind(), &RedisProtocolErrorKind::DecodeError);
+    assert_eq!(e.context(), None);
+  }
+
+  #[test]
+  fn should_create_buf_too_small_error() {
+    let e = RedisProtocolError::new(RedisProtocolErrorKind::BufferTooSmall(10), "foo");
+    let s = e.to_string();
+
+    assert_eq!(e.description(), "foo");
+    assert_eq!(e.kind(), &RedisProtocolErrorKind::BufferTooSmall(10));
+    assert_eq!(e.context(), None);
   }
 
   #[test]
@@ -574,6 +2203,17 @@ mod tests {
     assert_eq!(e.context(), Some(&ZEROED_KB[0..10]))
   }
 
+  #[test]
+  fn should_convert_error_into_owned() {
+    let n = NomError::Failure(Context::Code(&ZEROED_KB[0..10], NomErrorKind::Custom(1)));
+    let e = RedisProtocolError::from(n);
+    let owned = e.clone().into_owned();
+
+    assert_eq!(owned.kind(), e.kind());
+    assert_eq!(owned.to_string(), e.to_string());
+    assert_eq!(owned.context(), None);
+  }
+
   #[test]
   fn should_cast_from_nom_incomplete() {
     let n = NomError::Incomplete(Needed::Size(10));
@@ -582,6 +2222,19 @@ mod tests {
     assert_eq!(e.kind(), &RedisProtocolErrorKind::BufferTooSmall(10));
   }
 
+  #[test]
+  fn should_check_null_forms() {
+    assert!(Frame::Null.is_null());
+    assert!(Frame::NullResp3.is_null());
+    assert!(::decode::decode(b"$-1\r\n").unwrap().0.unwrap().is_null());
+    assert!(::decode::decode(b"*-1\r\n").unwrap().0.unwrap().is_null());
+    assert!(::decode::decode(b"_\r\n").unwrap().0.unwrap().is_null());
+
+    assert!(!Frame::BulkString(vec![]).is_null());
+    assert!(!Frame::Array(vec![]).is_null());
+    assert!(!Frame::Array(vec![Frame::Null]).is_null());
+  }
+
   #[test]
   fn should_check_frame_types() {
     let f = Frame::Null;
@@ -649,6 +2302,887 @@ mod tests {
     assert!(f.is_moved_or_ask_error());
   }
 
+  #[test]
+  fn should_read_frame_as_integer() {
+    assert_eq!(Frame::Integer(5).as_integer(), Some(5));
+    assert_eq!(Frame::BulkString("5".into()).as_integer(), Some(5));
+    assert_eq!(Frame::SimpleString("-10".into()).as_integer(), Some(-10));
+    assert_eq!(Frame::BulkString("foo".into()).as_integer(), None);
+    assert_eq!(Frame::Array(vec![Frame::Integer(5)]).as_integer(), None);
+  }
+
+  #[test]
+  fn should_truncate_large_bulk_string_for_logging() {
+    let frame = Frame::Array(vec![
+      Frame::BulkString("SET".into()),
+      Frame::BulkString(vec![b'a'; 1000])
+    ]);
+    let expected = Frame::Array(vec![
+      Frame::BulkString("SET".into()),
+      Frame::BulkString("aaaaaaaaaaaaaaaa...(+984 bytes)".into())
+    ]);
+
+    assert_eq!(frame.truncate_for_logging(16), expected);
+    assert_eq!(frame.truncate_for_logging(1000), frame);
+  }
+
+  #[test]
+  fn should_append_args() {
+    let mut frame = command(&["SET", "k", "v"]);
+    frame.append_arg("EX").unwrap();
+    frame.append_arg(100.to_string()).unwrap();
+
+    assert_eq!(frame, command(&["SET", "k", "v", "EX", "100"]));
+    assert_eq!(
+      frame.encode_to_bytes().unwrap(),
+      Bytes::from("*5\r\n$3\r\nSET\r\n$1\r\nk\r\n$1\r\nv\r\n$2\r\nEX\r\n$3\r\n100\r\n")
+    );
+  }
+
+  #[test]
+  fn should_error_appending_arg_to_non_array() {
+    let mut frame = Frame::BulkString("SET".into());
+    let e = frame.append_arg("EX").unwrap_err();
+
+    assert_eq!(e.kind(), &RedisProtocolErrorKind::EncodeError);
+    assert_eq!(frame, Frame::BulkString("SET".into()));
+  }
+
+  #[test]
+  fn should_loosely_compare_empty_and_null_arrays() {
+    assert!(Frame::Array(vec![]).loose_eq(&Frame::Null));
+    assert!(Frame::Null.loose_eq(&Frame::Array(vec![])));
+    assert!(Frame::Array(vec![]).loose_eq(&Frame::NullResp3));
+    assert!(!Frame::Array(vec![Frame::Null]).loose_eq(&Frame::Null));
+    assert_ne!(Frame::Array(vec![]), Frame::Null);
+  }
+
+  #[test]
+  fn should_loosely_compare_null_forms() {
+    assert!(Frame::Null.loose_eq(&Frame::NullResp3));
+    assert!(Frame::NullResp3.loose_eq(&Frame::Null));
+    assert!(!Frame::Null.loose_eq(&Frame::BulkString(vec![])));
+    assert_ne!(Frame::Null, Frame::NullResp3);
+  }
+
+  #[test]
+  fn should_loosely_compare_nested_frames() {
+    let a = Frame::Array(vec![Frame::SimpleString("OK".into()), Frame::Array(vec![Frame::Null])]);
+    let b = Frame::Array(vec![Frame::BulkString("OK".into()), Frame::Array(vec![Frame::NullResp3])]);
+
+    assert!(a.loose_eq(&b));
+    assert!(!a.loose_eq(&Frame::Array(vec![Frame::BulkString("OK".into())])));
+    assert!(!Frame::Integer(1).loose_eq(&Frame::Integer(2)));
+  }
+
+  #[test]
+  fn should_encode_reordered_sets_to_same_canonical_bytes() {
+    let a = Frame::Set(vec![Frame::Integer(2), Frame::BulkString("foo".into()), Frame::Integer(1)]);
+    let b = Frame::Set(vec![Frame::BulkString("foo".into()), Frame::Integer(1), Frame::Integer(2)]);
+
+    assert_ne!(a, b);
+    assert_eq!(a.canonical_bytes().unwrap(), b.canonical_bytes().unwrap());
+    assert_ne!(a.canonical_bytes().unwrap(), Frame::Set(vec![Frame::Integer(1)]).canonical_bytes().unwrap());
+  }
+
+  #[test]
+  fn should_encode_loosely_equal_frames_to_same_canonical_bytes() {
+    let a = Frame::Array(vec![Frame::SimpleString("OK".into()), Frame::Array(vec![Frame::Null])]);
+    let b = Frame::Array(vec![Frame::BulkString("OK".into()), Frame::Array(vec![Frame::NullResp3])]);
+    assert_eq!(a.canonical_bytes().unwrap(), b.canonical_bytes().unwrap());
+    assert_eq!(Frame::Array(vec![]).canonical_bytes().unwrap(), Frame::NullResp3.canonical_bytes().unwrap());
+
+    let a = Frame::Map(vec![(Frame::SimpleString("b".into()), Frame::Integer(2)), (Frame::BulkString("a".into()), Frame::Null)]);
+    let b = Frame::Map(vec![(Frame::BulkString("a".into()), Frame::NullResp3), (Frame::BulkString("b".into()), Frame::Integer(2))]);
+    assert_eq!(a.canonical_bytes().unwrap(), b.canonical_bytes().unwrap());
+    assert_eq!(a.canonical_bytes().unwrap(), Bytes::from("%2\r\n$1\r\na\r\n$-1\r\n$1\r\nb\r\n:2\r\n"));
+  }
+
+  #[test]
+  fn should_uppercase_command() {
+    let mut frame = command(&["get", "Key"]);
+    frame.uppercase_command();
+    assert_eq!(frame, command(&["GET", "Key"]));
+
+    let mut frame = Frame::Array(vec![Frame::SimpleString("Ping".into())]);
+    frame.uppercase_command();
+    assert_eq!(frame, Frame::Array(vec![Frame::SimpleString("PING".into())]));
+  }
+
+  #[test]
+  fn should_not_uppercase_non_command() {
+    let mut frame = Frame::BulkString("get".into());
+    frame.uppercase_command();
+    assert_eq!(frame, Frame::BulkString("get".into()));
+
+    let mut frame = Frame::Array(vec![Frame::Integer(1), Frame::BulkString("get".into())]);
+    frame.uppercase_command();
+    assert_eq!(frame, Frame::Array(vec![Frame::Integer(1), Frame::BulkString("get".into())]));
+
+    let mut frame = command(&[]);
+    frame.uppercase_command();
+    assert_eq!(frame, command(&[]));
+  }
+
+  #[test]
+  fn should_count_command_args() {
+    assert_eq!(command(&["GET", "k"]).command_arg_count(), Some(1));
+    assert_eq!(command(&["PING"]).command_arg_count(), Some(0));
+    assert_eq!(command(&[]).command_arg_count(), None);
+    assert_eq!(Frame::BulkString("GET".into()).command_arg_count(), None);
+  }
+
+  #[test]
+  fn should_check_command_arity() {
+    assert!(command(&["GET", "k"]).check_arity(1, Some(1)).is_ok());
+    assert!(command(&["DEL", "a", "b", "c"]).check_arity(1, None).is_ok());
+
+    let e = command(&["GET"]).check_arity(1, Some(1)).unwrap_err();
+    assert_eq!(e.kind(), &RedisProtocolErrorKind::EncodeError);
+    assert_eq!(e.description(), "Wrong number of arguments for 'get' command.");
+
+    assert!(command(&["GET", "a", "b"]).check_arity(1, Some(1)).is_err());
+    assert!(Frame::Integer(1).check_arity(0, None).is_err());
+  }
+
+  #[test]
+  fn should_read_first_key() {
+    assert_eq!(command(&["GET", "mykey"]).first_key(), Some(&b"mykey"[..]));
+    assert_eq!(command(&["SET", "foo", "bar"]).first_key(), Some(&b"foo"[..]));
+    assert_eq!(command(&["PING"]).first_key(), None);
+    assert_eq!(Frame::Array(vec![]).first_key(), None);
+    assert_eq!(Frame::Array(vec![Frame::BulkString("INCR".into()), Frame::Integer(1)]).first_key(), None);
+    assert_eq!(Frame::BulkString("mykey".into()).first_key(), None);
+  }
+
+  #[test]
+  fn should_read_simple_string_first_key() {
+    let (frame, _) = ::decode::decode(b"*2\r\n$3\r\nGET\r\n+key\r\n").unwrap();
+    assert_eq!(frame.unwrap().first_key(), Some(&b"key"[..]));
+  }
+
+  #[test]
+  fn should_read_arg_as_integer() {
+    let frame = command(&["EXPIRE", "k", "100"]);
+    assert_eq!(frame.arg_as_integer(2).unwrap(), 100);
+    assert_eq!(command(&["INCRBY", "k", "-5"]).arg_as_integer(2).unwrap(), -5);
+
+    let e = frame.arg_as_integer(3).unwrap_err();
+    assert_eq!(e.description(), "Missing argument at index 3.");
+  }
+
+  #[test]
+  fn should_error_reading_invalid_arg_as_integer() {
+    let e = command(&["EXPIRE", "k", "soon"]).arg_as_integer(2).unwrap_err();
+    assert_eq!(e.kind(), &RedisProtocolErrorKind::Unknown);
+    assert_eq!(e.description(), "Invalid integer argument at index 2.");
+
+    assert!(command(&["EXPIRE", "k", "1.5"]).arg_as_integer(2).is_err());
+    assert!(Frame::Array(vec![Frame::Integer(1)]).arg_as_integer(0).is_err());
+    assert!(Frame::Integer(1).arg_as_integer(0).is_err());
+  }
+
+  #[test]
+  fn should_concat_arrays() {
+    let frames = vec![
+      command(&["a", "b"]),
+      Frame::Array(vec![]),
+      command(&["c"])
+    ];
+
+    assert_eq!(Frame::concat_arrays(frames).unwrap(), command(&["a", "b", "c"]));
+    assert_eq!(Frame::concat_arrays(vec![]).unwrap(), Frame::Array(vec![]));
+  }
+
+  #[test]
+  fn should_error_concatenating_non_array() {
+    let e = Frame::concat_arrays(vec![command(&["a"]), Frame::Null]).unwrap_err();
+
+    assert_eq!(e.kind(), &RedisProtocolErrorKind::Unknown);
+    assert_eq!(e.description(), "Expected array frame, found Null.");
+  }
+
+  #[test]
+  fn should_wrap_frames_into_array() {
+    assert_eq!(Frame::Integer(1).into_array(), Frame::Array(vec![Frame::Integer(1)]));
+    assert_eq!(Frame::Null.into_array(), Frame::Array(vec![Frame::Null]));
+    assert_eq!(command(&["GET", "foo"]).into_array(), command(&["GET", "foo"]));
+    assert_eq!(Frame::Array(vec![]).into_array(), Frame::Array(vec![]));
+  }
+
+  #[test]
+  fn should_unwrap_single_element_array() {
+    assert_eq!(Frame::Array(vec![Frame::Integer(1)]).unwrap_single(), Frame::Integer(1));
+    assert_eq!(Frame::Integer(1).into_array().unwrap_single(), Frame::Integer(1));
+    assert_eq!(command(&["GET", "foo"]).unwrap_single(), command(&["GET", "foo"]));
+    assert_eq!(Frame::Array(vec![]).unwrap_single(), Frame::Array(vec![]));
+    assert_eq!(Frame::Set(vec![Frame::Integer(1)]).unwrap_single(), Frame::Set(vec![Frame::Integer(1)]));
+  }
+
+  #[test]
+  fn should_retain_array_elements() {
+    let mut frame = command(&["foo", "internal:bar", "baz", "internal:"]);
+    frame.retain_array(|f| !f.as_str().map(|s| s.starts_with("internal:")).unwrap_or(false));
+
+    assert_eq!(frame, command(&["foo", "baz"]));
+    assert_eq!(frame.encode_to_bytes().unwrap(), Bytes::from("*2\r\n$3\r\nfoo\r\n$3\r\nbaz\r\n"));
+  }
+
+  #[test]
+  fn should_not_retain_non_array() {
+    let mut frame = Frame::BulkString("internal:foo".into());
+    frame.retain_array(|_| false);
+
+    assert_eq!(frame, Frame::BulkString("internal:foo".into()));
+  }
+
+  #[test]
+  fn should_sort_array_of_bulk_strings() {
+    let mut first = Frame::Array(vec![
+      Frame::BulkString("c".into()),
+      Frame::BulkString("a".into()),
+      Frame::BulkString("b".into())
+    ]);
+    let mut second = Frame::Array(vec![
+      Frame::BulkString("b".into()),
+      Frame::BulkString("c".into()),
+      Frame::BulkString("a".into())
+    ]);
+    assert_ne!(first, second);
+
+    first.sort_array();
+    second.sort_array();
+    assert_eq!(first, second);
+    assert_eq!(first, Frame::Array(vec![
+      Frame::BulkString("a".into()),
+      Frame::BulkString("b".into()),
+      Frame::BulkString("c".into())
+    ]));
+  }
+
+  #[test]
+  fn should_sort_nested_arrays_by_full_encoding() {
+    let mut frame = Frame::Array(vec![
+      Frame::Array(vec![Frame::BulkString("a".into()), Frame::BulkString("c".into())]),
+      Frame::Array(vec![Frame::BulkString("a".into()), Frame::BulkString("b".into())]),
+      Frame::BulkString("z".into())
+    ]);
+    frame.sort_array();
+
+    assert_eq!(frame, Frame::Array(vec![
+      Frame::BulkString("z".into()),
+      Frame::Array(vec![Frame::BulkString("a".into()), Frame::BulkString("b".into())]),
+      Frame::Array(vec![Frame::BulkString("a".into()), Frame::BulkString("c".into())])
+    ]));
+  }
+
+  #[test]
+  fn should_not_sort_non_array() {
+    let mut frame = Frame::BulkString("foo".into());
+    frame.sort_array();
+    assert_eq!(frame, Frame::BulkString("foo".into()));
+  }
+
+  #[test]
+  #[cfg(feature = "json")]
+  fn should_convert_scalars_to_json() {
+    assert_eq!(Frame::SimpleString("OK".into()).to_json_value(), json!("OK"));
+    assert_eq!(Frame::BulkString("foo".into()).to_json_value(), json!("foo"));
+    assert_eq!(Frame::Integer(-42).to_json_value(), json!(-42));
+    assert_eq!(Frame::Double(1.5).to_json_value(), json!(1.5));
+    assert_eq!(Frame::Double(f64::INFINITY).to_json_value(), json!("inf"));
+    assert_eq!(Frame::Boolean(true).to_json_value(), json!(true));
+    assert_eq!(Frame::Null.to_json_value(), json!(null));
+    assert_eq!(Frame::NullResp3.to_json_value(), json!(null));
+  }
+
+  #[test]
+  #[cfg(feature = "json")]
+  fn should_convert_errors_to_json() {
+    assert_eq!(Frame::Error("ERR foo".into()).to_json_value(), json!({ "error": "ERR foo" }));
+    assert_eq!(Frame::Moved("MOVED 3999 127.0.0.1:6381".into()).to_json_value(), json!({ "error": "MOVED 3999 127.0.0.1:6381" }));
+    assert_eq!(Frame::Ask("ASK 3999 127.0.0.1:6381".into()).to_json_value(), json!({ "error": "ASK 3999 127.0.0.1:6381" }));
+  }
+
+  #[test]
+  #[cfg(feature = "json")]
+  fn should_convert_non_utf8_bulk_string_to_lossy_json() {
+    let frame = Frame::BulkString(vec![b'f', 0xff, b'o']);
+    assert_eq!(frame.to_json_value(), json!({ "string": "f\u{fffd}o", "lossy": true }));
+  }
+
+  #[test]
+  #[cfg(feature = "json")]
+  fn should_convert_map_to_json() {
+    let frame = Frame::Map(vec![
+      (Frame::SimpleString("foo".into()), Frame::Set(vec![Frame::Integer(1)])),
+      (Frame::Integer(2), Frame::Boolean(true))
+    ]);
+    assert_eq!(frame.to_json_value(), json!({ "foo": [1], "2": true }));
+  }
+
+  #[test]
+  #[cfg(feature = "json")]
+  fn should_convert_nested_array_to_json() {
+    let frame = Frame::Array(vec![
+      Frame::BulkString("foo".into()),
+      Frame::Array(vec![Frame::Integer(1), Frame::Null]),
+      Frame::Error("ERR bar".into())
+    ]);
+    assert_eq!(frame.to_json_value(), json!(["foo", [1, null], { "error": "ERR bar" }]));
+  }
+
+  #[test]
+  fn should_create_typed_error() {
+    let frame = Frame::typed_error("WRONGTYPE", "Operation against a key holding the wrong kind of value").unwrap();
+    assert_eq!(frame, Frame::Error("WRONGTYPE Operation against a key holding the wrong kind of value".into()));
+  }
+
+  #[test]
+  fn should_create_typed_error_without_message() {
+    let frame = Frame::typed_error("NOAUTH", "").unwrap();
+    assert_eq!(frame, Frame::Error("NOAUTH".into()));
+    assert_eq!(frame.encode_to_bytes().unwrap(), Bytes::from("-NOAUTH\r\n"));
+  }
+
+  #[test]
+  fn should_reject_invalid_typed_error_code() {
+    for code in ["", "wrongtype", "WRONG TYPE", " ERR", "ERR\r\n"].iter() {
+      let e = Frame::typed_error(code, "foo").unwrap_err();
+      assert_eq!(e.kind(), &RedisProtocolErrorKind::EncodeError);
+      assert_eq!(e.to_string(), "Encode Error: Invalid error code.");
+    }
+  }
+
+  #[test]
+  fn should_reject_typed_error_message_with_newline() {
+    let e = Frame::typed_error("ERR", "foo\r\n+OK").unwrap_err();
+    assert_eq!(e.to_string(), "Encode Error: Invalid error message.");
+  }
+
+  #[test]
+  fn should_detect_ok_status() {
+    let frame = Frame::SimpleString("OK".into());
+    assert!(frame.is_ok());
+    assert_eq!(frame.status(), Some("OK"));
+  }
+
+  #[test]
+  fn should_read_status_with_value() {
+    let frame = Frame::SimpleString("OK loaded".into());
+    assert!(!frame.is_ok());
+    assert_eq!(frame.status(), Some("OK loaded"));
+  }
+
+  #[test]
+  fn should_not_read_status_from_non_status_frame() {
+    let frame = Frame::BulkString("OK".into());
+    assert!(!frame.is_ok());
+    assert_eq!(frame.status(), None);
+    assert_eq!(Frame::Error("OK".into()).status(), None);
+  }
+
+  #[test]
+  fn should_read_frames_as_f64() {
+    assert_eq!(Frame::BulkString("3.25".into()).as_f64(), Some(3.25));
+    assert_eq!(Frame::BulkString("inf".into()).as_f64(), Some(f64::INFINITY));
+    assert_eq!(Frame::SimpleString("-inf".into()).as_f64(), Some(f64::NEG_INFINITY));
+    assert!(Frame::BulkString("nan".into()).as_f64().unwrap().is_nan());
+    assert_eq!(Frame::Integer(-3).as_f64(), Some(-3.0));
+    assert_eq!(Frame::Double(2.5).as_f64(), Some(2.5));
+    assert_eq!(Frame::BigNumber("12345678901234567890".into()).as_f64(), Some(12345678901234567890.0));
+  }
+
+  #[test]
+  fn should_not_read_non_numeric_frames_as_f64() {
+    assert_eq!(Frame::BulkString("foo".into()).as_f64(), None);
+    assert_eq!(Frame::BulkString(vec![0xff]).as_f64(), None);
+    assert_eq!(Frame::Null.as_f64(), None);
+    assert_eq!(Frame::Array(vec![Frame::Integer(1)]).as_f64(), None);
+  }
+
+  #[test]
+  fn should_read_optional_index() {
+    assert_eq!(Frame::Integer(5).as_optional_index(), Some(Some(5)));
+    assert_eq!(Frame::Integer(0).as_optional_index(), Some(Some(0)));
+    assert_eq!(Frame::Integer(-1).as_optional_index(), Some(None));
+    assert_eq!(Frame::Null.as_optional_index(), Some(None));
+    assert_eq!(Frame::NullResp3.as_optional_index(), Some(None));
+    assert_eq!(Frame::Integer(-2).as_optional_index(), None);
+    assert_eq!(Frame::BulkString("5".into()).as_optional_index(), None);
+  }
+
+  #[test]
+  fn should_detect_queued_reply() {
+    assert!(Frame::SimpleString("QUEUED".into()).is_queued());
+    assert!(!Frame::SimpleString("OK".into()).is_queued());
+    assert!(!Frame::BulkString("QUEUED".into()).is_queued());
+  }
+
+  #[test]
+  fn should_match_aborted_exec_reply_as_null() {
+    let (frame, _) = ::decode::decode(b"*-1\r\n").unwrap();
+    let frame = frame.unwrap();
+
+    assert!(frame.is_null());
+    assert!(!frame.is_queued());
+  }
+
+  #[test]
+  fn should_build_transaction() {
+    let commands = vec![
+      Frame::Array(vec![Frame::BulkString("INCR".into()), Frame::BulkString("foo".into())]),
+      Frame::Array(vec![Frame::BulkString("GET".into()), Frame::BulkString("foo".into())])
+    ];
+    let frames = Frame::transaction(commands.clone());
+
+    assert_eq!(frames.len(), 4);
+    assert_eq!(&frames[1..3], &commands[..]);
+    assert_eq!(frames[0].encode_to_bytes().unwrap(), Bytes::from("*1\r\n$5\r\nMULTI\r\n"));
+    assert_eq!(frames[3].encode_to_bytes().unwrap(), Bytes::from("*1\r\n$4\r\nEXEC\r\n"));
+  }
+
+  #[test]
+  fn should_dedup_frames_in_btree_set() {
+    let mut set = BTreeSet::new();
+    set.insert(command(&["GET", "foo"]));
+    set.insert(Frame::Integer(2));
+    set.insert(command(&["GET", "foo"]));
+    set.insert(Frame::Integer(2));
+    set.insert(Frame::Null);
+    set.insert(Frame::Null);
+
+    assert_eq!(set.len(), 3);
+  }
+
+  #[test]
+  fn should_order_frames_by_kind_then_content() {
+    let set: BTreeSet<Frame> = vec![
+      Frame::Array(vec![Frame::BulkString("b".into())]),
+      Frame::Integer(10),
+      Frame::Array(vec![Frame::BulkString("a".into()), Frame::BulkString("c".into())]),
+      Frame::SimpleString("OK".into()),
+      Frame::Integer(-3),
+      Frame::Array(vec![Frame::BulkString("a".into())]),
+      Frame::Double(1.5),
+      Frame::Double(f64::NAN),
+      Frame::Double(f64::NEG_INFINITY),
+    ].into_iter().collect();
+
+    let expected = vec![
+      Frame::SimpleString("OK".into()),
+      Frame::Integer(-3),
+      Frame::Integer(10),
+      Frame::Array(vec![Frame::BulkString("a".into())]),
+      Frame::Array(vec![Frame::BulkString("a".into()), Frame::BulkString("c".into())]),
+      Frame::Array(vec![Frame::BulkString("b".into())]),
+      Frame::Double(f64::NEG_INFINITY),
+      Frame::Double(1.5),
+      Frame::Double(f64::NAN),
+    ];
+    assert_eq!(set.into_iter().collect::<Vec<_>>(), expected);
+  }
+
+  #[test]
+  fn should_compare_nan_doubles_equal() {
+    assert_eq!(Frame::Double(f64::NAN), Frame::Double(f64::NAN));
+    assert_ne!(Frame::Double(0.0), Frame::Double(-0.0));
+    assert!(Frame::Double(-0.0) < Frame::Double(0.0));
+    assert_eq!(Frame::Double(-f64::NAN), Frame::Double(f64::NAN));
+    assert_ne!(Frame::Double(f64::NAN), Frame::Double(f64::INFINITY));
+    assert!(Frame::Double(f64::INFINITY) < Frame::Double(f64::NAN));
+  }
+
+  #[test]
+  fn should_build_checked_bulk_string() {
+    assert_eq!(Frame::bulk_string_checked(3, "foo".into()).unwrap(), Frame::BulkString("foo".into()));
+    assert_eq!(Frame::bulk_string_checked(0, Vec::new()).unwrap(), Frame::BulkString(Vec::new()));
+  }
+
+  #[test]
+  fn should_error_building_bulk_string_with_wrong_len() {
+    let e = Frame::bulk_string_checked(4, "foo".into()).unwrap_err();
+
+    assert_eq!(e.kind(), &RedisProtocolErrorKind::EncodeError);
+    assert_eq!(e.to_string(), "Encode Error: Expected bulk string with 4 bytes, found 3.");
+  }
+
+  #[test]
+  fn should_promote_large_integers_to_bignumber() {
+    let frame = Frame::integer_or_bignumber(42);
+    assert_eq!(frame, Frame::Integer(42));
+    assert_eq!(frame.encode_to_bytes().unwrap(), Bytes::from(":42\r\n"));
+
+    let frame = Frame::integer_or_bignumber(i64::MIN as i128);
+    assert_eq!(frame, Frame::Integer(i64::MIN));
+
+    let frame = Frame::integer_or_bignumber(i64::MAX as i128 + 1);
+    assert_eq!(frame, Frame::BigNumber("9223372036854775808".into()));
+    assert_eq!(frame.encode_to_bytes().unwrap(), Bytes::from("(9223372036854775808\r\n"));
+
+    let frame = Frame::integer_or_bignumber(i64::MIN as i128 - 1);
+    assert_eq!(frame.encode_to_bytes().unwrap(), Bytes::from("(-9223372036854775809\r\n"));
+  }
+
+  #[test]
+  fn should_read_byte_len_equal_to_encoded_len() {
+    let frames = vec![
+      Frame::SimpleString("OK".into()),
+      Frame::Error("ERR foo".into()),
+      Frame::Integer(-1234),
+      Frame::Integer(9_999_999_999_999_999),
+      Frame::Integer(-999_999_999_999_999_999),
+      Frame::Integer(i64::MIN),
+      Frame::BulkString("foo".into()),
+      Frame::BulkString(vec![]),
+      Frame::Null,
+      Frame::NullResp3,
+      Frame::Double(1.5),
+      Frame::Boolean(true),
+      Frame::BigNumber("12345678901234567890".into()),
+      command(&["SET", "foo", "bar"]),
+      Frame::Array(vec![command(&["a"]), Frame::Array(vec![]), Frame::Null, Frame::Array(vec![command(&["b", "c"])])]),
+      Frame::Array(vec![Frame::Integer(1), Frame::SimpleString("OK".into()), Frame::Double(-0.5)]),
+      Frame::Map(vec![(Frame::SimpleString("a".into()), Frame::Set(vec![Frame::Integer(1), Frame::Double(2.5)]))]),
+    ];
+
+    for frame in frames.iter() {
+      let mut buf = vec![0; 256];
+      let len = encode::encode(&mut buf, frame).unwrap();
+
+      assert_eq!(frame.byte_len(), len, "byte len matches for {:?}", frame);
+      assert_eq!(frame.byte_len(), frame.encode_to_bytes().unwrap().len());
+    }
+  }
+
+  #[test]
+  fn should_build_hello() {
+    let frame = Frame::hello(3, None, None);
+    assert_eq!(frame.encode_to_bytes().unwrap(), Bytes::from("*2\r\n$5\r\nHELLO\r\n$1\r\n3\r\n"));
+  }
+
+  #[test]
+  fn should_build_hello_with_auth_and_name() {
+    assert_eq!(Frame::hello(3, Some(("user", "pass")), None), command(&["HELLO", "3", "AUTH", "user", "pass"]));
+    assert_eq!(
+      Frame::hello(2, Some(("user", "pass")), Some("conn")),
+      command(&["HELLO", "2", "AUTH", "user", "pass", "SETNAME", "conn"])
+    );
+    assert_eq!(Frame::hello(3, None, Some("conn")), command(&["HELLO", "3", "SETNAME", "conn"]));
+  }
+
+  #[test]
+  fn should_validate_nested_frame() {
+    let frame = Frame::Push(vec![
+      command(&["GET", "foo"]),
+      Frame::Map(vec![(Frame::SimpleString("k".into()), Frame::Array(vec![Frame::Null, Frame::BulkString("a\r\nb".into())]))]),
+      Frame::Moved("MOVED 3999 127.0.0.1:6381".into()),
+      Frame::Ask("ASK 3999 127.0.0.1:6381".into()),
+      Frame::BigNumber("-123".into()),
+      Frame::Array(vec![Frame::Integer(1), Frame::SimpleString("OK".into()), Frame::Boolean(true)])
+    ]);
+
+    assert!(frame.validate().is_ok());
+  }
+
+  #[test]
+  fn should_fail_validating_simplestring_with_crlf() {
+    let e = Frame::SimpleString("a\r\nb".into()).validate().unwrap_err();
+    assert_eq!(e.kind(), &RedisProtocolErrorKind::EncodeError);
+
+    let frame = Frame::Set(vec![Frame::Integer(1), Frame::Error("ERR\n".into())]);
+    assert!(frame.validate().is_err());
+  }
+
+  #[test]
+  fn should_fail_validating_malformed_redirection() {
+    let e = Frame::Moved("foo".into()).validate().unwrap_err();
+    assert_eq!(e.kind(), &RedisProtocolErrorKind::EncodeError);
+    assert_eq!(e.description(), "Invalid redirection.");
+
+    assert!(Frame::Ask("3999 127.0.0.1".into()).validate().is_err());
+    assert!(Frame::Ask("MOVED 3999 127.0.0.1:6381".into()).validate().is_err());
+    assert!(Frame::Moved("ASK 3999 127.0.0.1:6381".into()).validate().is_err());
+    assert!(Frame::Moved("3999 127.0.0.1:6381\r\n".into()).validate().is_err());
+  }
+
+  #[test]
+  fn should_validate_decoded_redirection() {
+    for bytes in [&b"-MOVED 3999 127.0.0.1:6381\r\n"[..], &b"-ASK 3999 127.0.0.1:6381\r\n"[..]].iter() {
+      let (frame, _) = ::decode::decode(*bytes).unwrap();
+      let frame = frame.unwrap();
+
+      assert!(frame.is_moved_or_ask_error());
+      assert!(frame.validate().is_ok());
+    }
+
+    assert!(Frame::Moved("3999 127.0.0.1:6381".into()).validate().is_ok());
+    assert!(Frame::Ask("3999 127.0.0.1:6381".into()).validate().is_ok());
+  }
+
+  #[test]
+  fn should_pretty_print_nested_frames() {
+    let frame = Frame::Array(vec![
+      Frame::BulkString("foo".into()),
+      Frame::Array(vec![Frame::Integer(1), Frame::Null]),
+      Frame::Map(vec![(Frame::SimpleString("k".into()), Frame::Set(vec![Frame::Boolean(true)]))])
+    ]);
+    let expected = [
+      "Array(3)",
+      "  BulkString \"foo\"",
+      "  Array(2)",
+      "    Integer 1",
+      "    Null",
+      "  Map(1)",
+      "    SimpleString \"k\"",
+      "      Set(1)",
+      "        Boolean true",
+      ""
+    ].join("\n");
+
+    assert_eq!(frame.pretty(2), expected);
+  }
+
+  #[test]
+  fn should_pretty_print_scalar_frame() {
+    assert_eq!(Frame::BulkString("a\r\nb".into()).pretty(2), "BulkString \"a\\r\\nb\"\n");
+    assert_eq!(Frame::Double(1.5).pretty(4), "Double 1.5\n");
+  }
+
+  #[test]
+  fn should_shrink_frame_to_fit() {
+    let mut inner = Vec::with_capacity(16);
+    inner.push(Frame::BulkString(Vec::with_capacity(64)));
+    let mut frames = Vec::with_capacity(32);
+    frames.push(Frame::Array(inner));
+
+    let (decoded, _) = ::decode::decode(b"*2\r\n$3\r\nfoo\r\n*1\r\n$1\r\na\r\n").unwrap();
+    frames.push(decoded.unwrap());
+
+    let mut frame = Frame::Array(frames);
+    frame.shrink_to_fit();
+
+    fn check_capacity(frame: &Frame) {
+      match *frame {
+        Frame::Array(ref frames) => {
+          assert_eq!(frames.capacity(), frames.len());
+          frames.iter().for_each(check_capacity);
+        },
+        Frame::BulkString(ref b) => assert_eq!(b.capacity(), b.len()),
+        _ => {}
+      }
+    }
+    check_capacity(&frame);
+  }
+
+  #[test]
+  fn should_map_bulk_strings() {
+    let mut frame = command(&["MSET", "a", "1", "b", "2"]);
+    frame.map_bulk_strings(|b| {
+      let mut out = b"t1:".to_vec();
+      out.extend_from_slice(b);
+      out
+    });
+
+    assert_eq!(frame, command(&["t1:MSET", "t1:a", "t1:1", "t1:b", "t1:2"]));
+    assert_eq!(
+      frame.encode_to_bytes().unwrap(),
+      Bytes::from("*5\r\n$7\r\nt1:MSET\r\n$4\r\nt1:a\r\n$4\r\nt1:1\r\n$4\r\nt1:b\r\n$4\r\nt1:2\r\n")
+    );
+  }
+
+  #[test]
+  fn should_map_nested_bulk_strings_only() {
+    let mut frame = Frame::Array(vec![
+      Frame::SimpleString("foo".into()),
+      Frame::Integer(1),
+      Frame::Array(vec![Frame::BulkString("bar".into()), Frame::Null])
+    ]);
+    frame.map_bulk_strings(|b| b.to_ascii_uppercase());
+
+    assert_eq!(frame, Frame::Array(vec![
+      Frame::SimpleString("foo".into()),
+      Frame::Integer(1),
+      Frame::Array(vec![Frame::BulkString("BAR".into()), Frame::Null])
+    ]));
+  }
+
+  #[test]
+  fn should_flatten_nested_bulk_strings() {
+    let frame = Frame::Array(vec![
+      Frame::BulkString("1-0".into()),
+      Frame::Array(vec![
+        Frame::BulkString("foo".into()),
+        Frame::Integer(1),
+        Frame::Null,
+        Frame::Array(vec![Frame::BulkString("bar".into())])
+      ]),
+      Frame::SimpleString("baz".into()),
+      Frame::BulkString("".into())
+    ]);
+    let expected: Vec<&[u8]> = vec![b"1-0", b"foo", b"bar", b""];
+
+    assert_eq!(frame.flatten_bulk(), expected);
+    assert!(Frame::Integer(1).flatten_bulk().is_empty());
+  }
+
+  #[test]
+  fn should_compute_stable_checksum() {
+    assert_eq!(Frame::SimpleString("OK".into()).checksum(), 0x18a923c7367632ab);
+
+    let frame = Frame::Array(vec![Frame::BulkString("GET".into()), Frame::BulkString("foo".into())]);
+    assert_eq!(frame.checksum(), frame.clone().checksum());
+
+    let mut hasher = utils::Fnv64::new();
+    hasher.write(b":9999999999999999\r\n");
+    assert_eq!(Frame::Integer(9_999_999_999_999_999).checksum(), hasher.finish());
+  }
+
+  #[test]
+  fn should_compute_checksum_of_encoded_bytes() {
+    let frames = [
+      Frame::SimpleString("OK".into()),
+      Frame::Error("ERR foo".into()),
+      Frame::Integer(-1),
+      Frame::Integer(i64::MIN),
+      Frame::BulkString("foo".into()),
+      Frame::Array(vec![Frame::BulkString("SET".into()), Frame::BulkString("foo".into()), Frame::Null]),
+      Frame::Moved("MOVED 3999 127.0.0.1:6381".into()),
+      Frame::Ask("ASK 3999 127.0.0.1:6381".into()),
+      Frame::Null,
+      Frame::NullResp3,
+      Frame::Double(1.5),
+      Frame::Boolean(false),
+      Frame::BigNumber("3492890328409238509324850943850943825024385".into()),
+      Frame::VerbatimString { format: VerbatimFormat::Txt, data: "foo".into() },
+      Frame::Map(vec![(Frame::SimpleString("foo".into()), Frame::Set(vec![Frame::Integer(-1), Frame::Double(1.5)]))]),
+      Frame::Set(vec![Frame::Integer(1)]),
+      Frame::Push(vec![Frame::Boolean(true), Frame::NullResp3, Frame::Error("ERR foo".into())]),
+      Frame::Attribute(vec![(Frame::SimpleString("ttl".into()), Frame::Integer(3600))]),
+      Frame::Array((0..20).map(|i| Frame::Integer(i * 1_000_000_007)).collect()),
+      Frame::Array(vec![Frame::Double(1e100), Frame::Double(-0.0), Frame::Double(f64::NAN), Frame::Integer(i64::MAX)]),
+      Frame::VerbatimString { format: VerbatimFormat::Markdown, data: vec![] }
+    ];
+
+    for frame in frames.iter() {
+      let mut hasher = utils::Fnv64::new();
+      hasher.write(&frame.encode_to_bytes().unwrap());
+      assert_eq!(frame.checksum(), hasher.finish());
+    }
+  }
+
+  #[test]
+  fn should_compute_different_checksums() {
+    assert_ne!(Frame::BulkString("foo".into()).checksum(), Frame::BulkString("bar".into()).checksum());
+    assert_ne!(Frame::BulkString("1".into()).checksum(), Frame::Integer(1).checksum());
+    assert_ne!(Frame::Null.checksum(), Frame::NullResp3.checksum());
+    assert_ne!(Frame::Integer(9_999_999_999_999_999).checksum(), Frame::Integer(99_999_999_999_999_999).checksum());
+    assert_ne!(Frame::Double(0.0).checksum(), Frame::Double(-0.0).checksum());
+    assert_ne!(
+      Frame::Array(vec![Frame::BulkString("a".into()), Frame::BulkString("b".into())]).checksum(),
+      Frame::Array(vec![Frame::BulkString("b".into()), Frame::BulkString("a".into())]).checksum()
+    );
+  }
+
+  #[test]
+  fn should_display_binary_bulk_string_lossy() {
+    let frame = Frame::BulkString(vec![b'f', 0xff, b'o']);
+
+    assert_eq!(frame.display_lossy().to_string(), "f\u{fffd}o");
+    assert_eq!(format!("{:?}", frame), "BulkString([102, 255, 111])");
+  }
+
+  #[test]
+  fn should_display_nested_frames_lossy() {
+    let frame = Frame::Array(vec![
+      Frame::BulkString("foo".into()),
+      Frame::Integer(1),
+      Frame::Null,
+      Frame::Map(vec![(Frame::SimpleString("bar".into()), Frame::Boolean(true))])
+    ]);
+
+    assert_eq!(frame.display_lossy().to_string(), "[foo, 1, nil, {bar: true}]");
+  }
+
+  fn command(args: &[&str]) -> Frame {
+    Frame::Array(args.iter().map(|a| Frame::BulkString(a.as_bytes().to_vec())).collect())
+  }
+
+  #[test]
+  fn should_extract_mset_keys() {
+    let frame = command(&["MSET", "k1", "v1", "k2", "v2"]);
+    let keys = frame.extract_keys(KeySpec::new(1, -1, 2));
+
+    assert_eq!(keys, vec![&b"k1"[..], &b"k2"[..]]);
+  }
+
+  #[test]
+  fn should_extract_del_keys() {
+    let frame = command(&["DEL", "a", "b", "c"]);
+    let keys = frame.extract_keys(KeySpec::new(1, -1, 1));
+
+    assert_eq!(keys, vec![&b"a"[..], &b"b"[..], &b"c"[..]]);
+  }
+
+  #[test]
+  fn should_extract_keys_with_bounded_last_key() {
+    let frame = command(&["RENAME", "a", "b"]);
+    assert_eq!(frame.extract_keys(KeySpec::new(1, 2, 1)), vec![&b"a"[..], &b"b"[..]]);
+    assert_eq!(frame.extract_keys(KeySpec::new(1, 1, 1)), vec![&b"a"[..]]);
+    assert_eq!(frame.extract_keys(KeySpec::new(1, 10, 1)), vec![&b"a"[..], &b"b"[..]]);
+  }
+
+  #[test]
+  fn should_extract_no_keys() {
+    let frame = command(&["PING"]);
+    assert!(frame.extract_keys(KeySpec::new(1, -1, 1)).is_empty());
+    assert!(frame.extract_keys(KeySpec::new(0, 0, 0)).is_empty());
+    assert!(Frame::BulkString("DEL".into()).extract_keys(KeySpec::new(1, -1, 1)).is_empty());
+  }
+
+  #[test]
+  fn should_find_same_slot_with_hash_tags() {
+    let frame = command(&["MSET", "{tag}a", "1", "{tag}b", "2"]);
+    let slot = frame.same_slot(KeySpec::new(1, -1, 2)).unwrap();
+
+    assert_eq!(slot, Some(utils::redis_keyslot("tag")));
+  }
+
+  #[test]
+  fn should_error_on_cross_slot_keys() {
+    let frame = command(&["MSET", "a", "1", "b", "2"]);
+    let e = frame.same_slot(KeySpec::new(1, -1, 2)).unwrap_err();
+
+    assert_eq!(e.to_string(), format!("Encode Error: Keys map to different slots: {} and {}.", utils::redis_keyslot("a"), utils::redis_keyslot("b")));
+  }
+
+  #[test]
+  fn should_find_no_slot_without_keys() {
+    let frame = command(&["PING"]);
+    assert_eq!(frame.same_slot(KeySpec::new(1, -1, 1)).unwrap(), None);
+  }
+
+  #[test]
+  fn should_truncate_simple_string_on_char_boundary_for_logging() {
+    let frame = Frame::SimpleString("aé".into());
+    assert_eq!(frame.truncate_for_logging(2), Frame::SimpleString("a...(+2 bytes)".into()));
+  }
+
+  #[test]
+  fn should_not_truncate_small_frames_for_logging() {
+    let frames = vec![
+      Frame::SimpleString("OK".into()),
+      Frame::BulkString("foo".into()),
+      Frame::Integer(123456789),
+      Frame::Error("ERR unknown command".into()),
+      Frame::Null
+    ];
+
+    for frame in frames.into_iter() {
+      assert_eq!(frame.truncate_for_logging(16), frame);
+    }
+  }
+
   #[test]
   fn should_decode_frame_kind_byte() {
     assert_eq!(FrameKind::from_byte(SIMPLESTRING_BYTE), Some(FrameKind::SimpleString));
@@ -656,6 +3190,11 @@ mod tests {
     assert_eq!(FrameKind::from_byte(BULKSTRING_BYTE), Some(FrameKind::BulkString));
     assert_eq!(FrameKind::from_byte(INTEGER_BYTE), Some(FrameKind::Integer));
     assert_eq!(FrameKind::from_byte(ARRAY_BYTE), Some(FrameKind::Array));
+    assert_eq!(FrameKind::from_byte(RESP3_NULL_BYTE), Some(FrameKind::NullResp3));
+    assert_eq!(FrameKind::from_byte(DOUBLE_BYTE), Some(FrameKind::Double));
+    assert_eq!(FrameKind::from_byte(BOOLEAN_BYTE), Some(FrameKind::Boolean));
+    assert_eq!(FrameKind::from_byte(BIGNUMBER_BYTE), Some(FrameKind::BigNumber));
+    assert_eq!(FrameKind::from_byte(VERBATIM_BYTE), Some(FrameKind::VerbatimString));
   }
 
   #[test]
@@ -665,6 +3204,11 @@ mod tests {
     assert_eq!(FrameKind::BulkString.to_byte(), BULKSTRING_BYTE);
     assert_eq!(FrameKind::Integer.to_byte(), INTEGER_BYTE);
     assert_eq!(FrameKind::Array.to_byte(), ARRAY_BYTE);
+    assert_eq!(FrameKind::NullResp3.to_byte(), RESP3_NULL_BYTE);
+    assert_eq!(FrameKind::Double.to_byte(), DOUBLE_BYTE);
+    assert_eq!(FrameKind::Boolean.to_byte(), BOOLEAN_BYTE);
+    assert_eq!(FrameKind::BigNumber.to_byte(), BIGNUMBER_BYTE);
+    assert_eq!(FrameKind::VerbatimString.to_byte(), VERBATIM_BYTE);
   }
 
   #[test]
@@ -694,4 +3238,63 @@ mod tests {
     assert_eq!(RedisProtocolErrorKind::BufferTooSmall(10).to_str(), "Buffer too small");
   }
 
+  #[test]
+  fn should_read_redis_type_names() {
+    let pairs = vec![
+      (Frame::SimpleString("OK".into()), "simple string"),
+      (Frame::Error("ERR foo".into()), "error"),
+      (Frame::Moved("3999 127.0.0.1:6381".into()), "error"),
+      (Frame::Ask("3999 127.0.0.1:6381".into()), "error"),
+      (Frame::Integer(1), "integer"),
+      (Frame::BulkString("foo".into()), "bulk string"),
+      (Frame::Array(vec![]), "array"),
+      (Frame::Null, "null"),
+      (Frame::NullResp3, "null"),
+      (Frame::Double(1.5), "double"),
+      (Frame::Boolean(true), "boolean"),
+      (Frame::BigNumber("3492890328409238509324850943850943825024385".into()), "big number"),
+      (Frame::VerbatimString { format: VerbatimFormat::Txt, data: "foo".into() }, "verbatim string"),
+      (Frame::Map(vec![]), "map"),
+      (Frame::Set(vec![]), "set"),
+      (Frame::Push(vec![]), "push"),
+      (Frame::Attribute(vec![]), "attribute")
+    ];
+
+    for (frame, name) in pairs.into_iter() {
+      assert_eq!(frame.redis_type_name(), name);
+    }
+  }
+
+  #[test]
+  fn should_normalize_simple_string_command_args() {
+    let (frame, _) = ::decode::decode(b"*2\r\n$3\r\nGET\r\n+key\r\n").unwrap();
+    let mut frame = frame.expect("Expected frame");
+    frame.normalize_command_args();
+
+    assert_eq!(frame, Frame::Array(vec![
+      Frame::BulkString("GET".into()),
+      Frame::BulkString("key".into())
+    ]));
+  }
+
+  #[test]
+  fn should_not_normalize_non_string_command_args() {
+    let mut frame = Frame::Array(vec![
+      Frame::SimpleString("INCRBY".into()),
+      Frame::BulkString("foo".into()),
+      Frame::Integer(1)
+    ]);
+    frame.normalize_command_args();
+
+    assert_eq!(frame, Frame::Array(vec![
+      Frame::BulkString("INCRBY".into()),
+      Frame::BulkString("foo".into()),
+      Frame::Integer(1)
+    ]));
+
+    let mut frame = Frame::SimpleString("OK".into());
+    frame.normalize_command_args();
+    assert_eq!(frame, Frame::SimpleString("OK".into()));
+  }
+
 }
\ No newline at end of file
@@ -8,22 +8,73 @@ use crc16::{
 
 use bytes::BytesMut;
 
+use std::str;
+use std::fmt;
+use std::fmt::Write;
+#[cfg(feature = "redirection-cache")]
+use std::sync::{
+  Mutex,
+  MutexGuard,
+  OnceLock
+};
+#[cfg(feature = "redirection-cache")]
+use std::collections::{
+  BTreeMap,
+  HashMap
+};
+use std::net::{
+  IpAddr,
+  SocketAddr
+};
+
 use cookie_factory::GenError;
 
 /// Terminating bytes between frames.
 pub const CRLF: &'static str = "\r\n";
 /// Byte representation of a `null` value.
 pub const NULL: &'static str = "$-1\r\n";
+/// Byte representation of a RESP3 `null` value.
+pub const RESP3_NULL: &'static str = "_\r\n";
+/// The header of a RESP3 streamed string.
+pub const STREAMED_STRING_START: &'static str = "$?\r\n";
+/// The zero length chunk that terminates a RESP3 streamed string.
+pub const STREAM_END: &'static str = ";0\r\n";
 
 pub const KB: usize = 1024;
 
+/// The number of bytes in the big endian length header written by `encode_length_prefixed`.
+pub const LENGTH_PREFIX_LEN: usize = 4;
+
 /// A pre-defined zeroed out KB of data, used to speed up extending buffers while encoding.
 pub const ZEROED_KB: &'static [u8; 1024] = &[0; 1024];
 
 const REDIS_CLUSTER_SLOTS: u16 = 16384;
 
+/// Custom nom error code used when a bulk string or array length prefix is malformed.
+pub const INVALID_LENGTH_ERROR: u32 = 1;
+/// Custom nom error code used when a line exceeds `DecodeOptions::max_line_len`.
+pub const LINE_TOO_LONG_ERROR: u32 = 2;
+/// Custom nom error code used when an integer line is empty or does not contain a valid integer, such as a bare sign.
+pub const INVALID_INTEGER_ERROR: u32 = 3;
+/// Custom nom error code used when aggregate frames are nested deeper than `DecodeOptions::max_depth`.
+pub const MAX_DEPTH_ERROR: u32 = 4;
+/// Custom nom error code used when `decode_scalar` reads the start of an aggregate frame.
+pub const NOT_SCALAR_ERROR: u32 = 5;
+/// Custom nom error code used when a verbatim string does not start with a three byte format followed by `:`.
+pub const INVALID_VERBATIM_ERROR: u32 = 6;
+/// Custom nom error code used when a set contains duplicate elements and `DecodeOptions::strict_sets` is enabled.
+pub const DUPLICATE_SET_ELEMENT_ERROR: u32 = 7;
+/// Custom nom error code used when a length prefix only contains digits but is too large for a `usize`.
+pub const LENGTH_OUT_OF_RANGE_ERROR: u32 = 8;
+/// Custom nom error code used when a streamed aggregate terminator line contains anything other than `.`.
+pub const INVALID_STREAM_END_ERROR: u32 = 9;
+
 const PUBSUB_PREFIX: &'static str = "message";
 const PATTERN_PUBSUB_PREFIX: &'static str = "pmessage";
+const SUBSCRIPTION_KINDS: [&'static str; 6] = ["subscribe", "psubscribe", "ssubscribe", "unsubscribe", "punsubscribe", "sunsubscribe"];
+const KEYSPACE_PREFIX: &'static str = "__keyspace@";
+const KEYEVENT_PREFIX: &'static str = "__keyevent@";
+const KEYSPACE_DB_SUFFIX: &'static str = "__:";
 
 #[inline]
 pub fn check_offset(x: &(&mut [u8], usize)) -> Result<(), GenError> {
@@ -37,11 +88,20 @@ pub fn check_offset(x: &(&mut [u8], usize)) -> Result<(), GenError> {
 /// Returns the number of bytes necessary to encode a string representation of `d`.
 #[inline]
 pub fn digits_in_number(d: usize) -> usize {
-  if d == 0 {
-    return 1;
+  digits_in_u64(d as u64)
+}
+
+// counted with integer division since `f64::log10` rounds values like `10^k - 1` above 2^53 up to the next power of ten
+#[inline]
+fn digits_in_u64(mut d: u64) -> usize {
+  let mut digits = 1;
+
+  while d >= 10 {
+    d /= 10;
+    digits += 1;
   }
 
-  ((d as f64).log10()).floor() as usize + 1
+  digits
 }
 
 #[inline]
@@ -49,13 +109,46 @@ pub fn bulkstring_encode_len(b: &[u8]) -> usize {
   1 + digits_in_number(b.len()) + 2 + b.len() + 2
 }
 
+/// The length of the `txt:` style format prefix of a verbatim string.
+pub const VERBATIM_FORMAT_LEN: usize = 4;
+
 #[inline]
-pub fn array_encode_len(frames: &Vec<Frame>) -> Result<usize, GenError> {
+pub fn verbatimstring_encode_len(b: &[u8]) -> usize {
+  let len = VERBATIM_FORMAT_LEN + b.len();
+  1 + digits_in_number(len) + 2 + len + 2
+}
+
+#[inline]
+pub fn array_encode_len(frames: &Vec<Frame>) -> usize {
   let padding = 1 + digits_in_number(frames.len()) + 2;
 
-  frames.iter().fold(Ok(padding), |m, f| {
-    m.and_then(|s| encode_len(f).map(|l| s + l))
-  })
+  frames.iter().fold(padding, |m, f| m + encode_len(f))
+}
+
+#[inline]
+pub fn map_encode_len(pairs: &[(Frame, Frame)]) -> usize {
+  let mut len = 1 + digits_in_number(pairs.len()) + 2;
+
+  for (key, value) in pairs.iter() {
+    len += encode_len(key) + encode_len(value);
+  }
+
+  len
+}
+
+/// Precomputed encodings of the integers from -1 to 9, which are the most common integer replies.
+const SMALL_INTEGERS: [&'static str; 11] = [
+  ":-1\r\n", ":0\r\n", ":1\r\n", ":2\r\n", ":3\r\n", ":4\r\n", ":5\r\n", ":6\r\n", ":7\r\n", ":8\r\n", ":9\r\n"
+];
+
+/// Returns the precomputed encoding of `i`, if available.
+#[inline]
+pub fn small_integer_encoding(i: i64) -> Option<&'static str> {
+  if (-1..=9).contains(&i) {
+    Some(SMALL_INTEGERS[(i + 1) as usize])
+  }else{
+    None
+  }
 }
 
 #[inline]
@@ -75,26 +168,67 @@ pub fn integer_encode_len(i: &i64) -> usize {
   }else{
     0
   };
-  let as_usize = if *i < 0 {
-    (*i * -1) as usize
+
+  1 + digits_in_u64(i.unsigned_abs()) + 2 + prefix
+}
+
+#[inline]
+pub fn bignumber_encode_len(s: &str) -> usize {
+  1 + s.len() + 2
+}
+
+/// Doubles with a magnitude at or above this are written with an exponent.
+const DOUBLE_MAX_DECIMAL: f64 = 1e16;
+/// Non-zero doubles with a magnitude below this are written with an exponent.
+const DOUBLE_MIN_DECIMAL: f64 = 1e-5;
+
+/// Returns the string representation of a double. Finite values use the shortest representation that parses back to the same value.
+///
+/// Whole numbers are written without a fractional part, so `3.0` is written as `3`, which matches the doubles sent by Redis.
+/// Very large and very small values are written with an exponent, so `1e100` is written as `1e100` rather than 101 digits.
+/// Negative zero keeps its sign (`-0`) and subnormal values round trip bit-exactly.
+pub fn double_to_string(d: f64) -> String {
+  let abs = d.abs();
+
+  if d.is_nan() {
+    "nan".into()
+  }else if abs.is_finite() && abs != 0.0 && (abs >= DOUBLE_MAX_DECIMAL || abs < DOUBLE_MIN_DECIMAL) {
+    format!("{:e}", d)
   }else{
-    *i as usize
-  };
+    d.to_string()
+  }
+}
 
-  1 + digits_in_number(as_usize) + 2 + prefix
+#[inline]
+pub fn double_encode_len(d: f64) -> usize {
+  1 + double_to_string(d).len() + 2
+}
+
+#[inline]
+pub fn boolean_encode_len() -> usize {
+  1 + 1 + 2
 }
 
 /// Returns the number of bytes necessary to represent the frame.
-pub fn encode_len(data: &Frame) -> Result<usize, GenError> {
+pub fn encode_len(data: &Frame) -> usize {
   match *data {
-    Frame::BulkString(ref b)   => Ok(bulkstring_encode_len(&b)),
+    Frame::BulkString(ref b)   => bulkstring_encode_len(&b),
     Frame::Array(ref frames)   => array_encode_len(frames),
-    Frame::Null                => Ok(NULL.as_bytes().len()),
-    Frame::SimpleString(ref s) => Ok(simplestring_encode_len(s)),
-    Frame::Error(ref s)        => Ok(error_encode_len(s)),
-    Frame::Integer(ref i)      => Ok(integer_encode_len(i)),
-    Frame::Moved(ref s)        => Ok(error_encode_len(s)),
-    Frame::Ask(ref s)          => Ok(error_encode_len(s))
+    Frame::Null                => NULL.as_bytes().len(),
+    Frame::NullResp3           => RESP3_NULL.as_bytes().len(),
+    Frame::Double(ref d)       => double_encode_len(*d),
+    Frame::Boolean(_)          => boolean_encode_len(),
+    Frame::BigNumber(ref s)    => bignumber_encode_len(s),
+    Frame::VerbatimString { ref data, .. } => verbatimstring_encode_len(data),
+    Frame::Set(ref frames)     => array_encode_len(frames),
+    Frame::Push(ref frames)    => array_encode_len(frames),
+    Frame::Map(ref pairs)      => map_encode_len(pairs),
+    Frame::Attribute(ref p)    => map_encode_len(p),
+    Frame::SimpleString(ref s) => simplestring_encode_len(s),
+    Frame::Error(ref s)        => error_encode_len(s),
+    Frame::Integer(ref i)      => integer_encode_len(i),
+    Frame::Moved(ref s)        => error_encode_len(s),
+    Frame::Ask(ref s)          => error_encode_len(s)
   }
 }
 
@@ -153,10 +287,271 @@ pub fn string_to_redirection(s: &str) -> Result<Redirection, RedisProtocolError>
   }
 }
 
+/// Parse `s` as a cluster redirection, using the shared `RedirectionCache` when the `redirection-cache` feature is enabled.
+#[cfg(feature = "redirection-cache")]
+pub fn parse_redirection(s: &str) -> Result<Redirection, RedisProtocolError<'static>> {
+  RedirectionCache::shared().parse(s)
+}
+
+/// Parse `s` as a cluster redirection, using the shared `RedirectionCache` when the `redirection-cache` feature is enabled.
+#[cfg(not(feature = "redirection-cache"))]
+pub fn parse_redirection(s: &str) -> Result<Redirection, RedisProtocolError<'_>> {
+  string_to_redirection(s)
+}
+
+#[cfg(feature = "redirection-cache")]
+#[derive(Debug, Default)]
+struct RedirectionCacheState {
+  // the redirection for each cached string and the tick at which it was last used
+  entries: HashMap<String, (Redirection, u64)>,
+  // the cached strings keyed by the tick at which they were last used, so the first entry is the least recently used
+  recency: BTreeMap<u64, String>,
+  tick: u64,
+  hits: usize,
+  misses: usize
+}
+
+/// The number of redirections kept by the shared cache used by `Frame::to_redirection`.
+#[cfg(feature = "redirection-cache")]
+pub const SHARED_REDIRECTION_CACHE_CAPACITY: usize = 256;
+
+#[cfg(feature = "redirection-cache")]
+static SHARED_REDIRECTION_CACHE: OnceLock<RedirectionCache> = OnceLock::new();
+
+/// A bounded, thread-safe LRU cache of parsed cluster redirections, keyed by the redirection string.
+///
+/// At most `capacity` redirections are kept, and the least recently used redirection is dropped when the cache is full.
+/// Invalid redirections are not cached.
+#[cfg(feature = "redirection-cache")]
+#[derive(Debug)]
+pub struct RedirectionCache {
+  state: Mutex<RedirectionCacheState>,
+  capacity: usize
+}
+
+#[cfg(feature = "redirection-cache")]
+impl RedirectionCache {
+
+  /// Create an empty cache that keeps at most `capacity` redirections. A cache with a capacity of zero never caches anything.
+  pub fn new(capacity: usize) -> RedirectionCache {
+    RedirectionCache {
+      state: Mutex::new(RedirectionCacheState::default()),
+      capacity
+    }
+  }
+
+  /// Read the cache shared by `Frame::to_redirection`, which keeps at most `SHARED_REDIRECTION_CACHE_CAPACITY` redirections.
+  pub fn shared() -> &'static RedirectionCache {
+    SHARED_REDIRECTION_CACHE.get_or_init(|| RedirectionCache::new(SHARED_REDIRECTION_CACHE_CAPACITY))
+  }
+
+  /// Parse `s` as a cluster redirection, returning the cached redirection if `s` was parsed recently.
+  pub fn parse(&self, s: &str) -> Result<Redirection, RedisProtocolError<'static>> {
+    let mut guard = self.lock();
+    let state = &mut *guard;
+    state.tick += 1;
+    let tick = state.tick;
+
+    if let Some(entry) = state.entries.get_mut(s) {
+      if let Some(key) = state.recency.remove(&entry.1) {
+        state.recency.insert(tick, key);
+      }
+      entry.1 = tick;
+
+      state.hits += 1;
+      return Ok(entry.0.clone());
+    }
+
+    state.misses += 1;
+    let redirection = string_to_redirection(s).map_err(|e| e.into_owned())?;
+
+    if self.capacity > 0 {
+      if state.entries.len() >= self.capacity {
+        if let Some((_, oldest)) = state.recency.pop_first() {
+          let _ = state.entries.remove(&oldest);
+        }
+      }
+
+      state.entries.insert(s.to_owned(), (redirection.clone(), tick));
+      state.recency.insert(tick, s.to_owned());
+    }
+
+    Ok(redirection)
+  }
+
+  /// Read the number of cached redirections.
+  pub fn len(&self) -> usize {
+    self.lock().entries.len()
+  }
+
+  /// Whether or not the cache is empty.
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// Read the number of calls to `parse` that returned a cached redirection.
+  pub fn hits(&self) -> usize {
+    self.lock().hits
+  }
+
+  /// Read the number of calls to `parse` that did not find a cached redirection.
+  pub fn misses(&self) -> usize {
+    self.lock().misses
+  }
+
+  /// Remove all cached redirections and reset the hit and miss counters.
+  pub fn clear(&self) {
+    let mut state = self.lock();
+
+    state.entries.clear();
+    state.recency.clear();
+    state.hits = 0;
+    state.misses = 0;
+  }
+
+  fn lock(&self) -> MutexGuard<RedirectionCacheState> {
+    // entries are only added once fully parsed, so a panic while the lock is held can't leave a bad entry behind
+    self.state.lock().unwrap_or_else(|e| e.into_inner())
+  }
+
+}
+
+pub fn host_to_socket_addr<'a>(host: &str, port: u16) -> Result<SocketAddr, RedisProtocolError<'a>> {
+  let host = if host.starts_with('[') && host.ends_with(']') {
+    &host[1..host.len() - 1]
+  }else{
+    host
+  };
+
+  match host.parse::<IpAddr>() {
+    Ok(ip) => Ok(SocketAddr::new(ip, port)),
+    Err(_) => Err(RedisProtocolError::new(RedisProtocolErrorKind::Unknown, "Invalid redirection host."))
+  }
+}
+
 /// Perform a crc16 XMODEM operation against a string slice.
 #[inline]
-fn crc16_xmodem(key: &str) -> u16 {
-  State::<XMODEM>::calculate(key.as_bytes()) % REDIS_CLUSTER_SLOTS
+fn crc16_xmodem(key: &[u8]) -> u16 {
+  State::<XMODEM>::calculate(key) % REDIS_CLUSTER_SLOTS
+}
+
+/// An incremental crc16 XMODEM hasher for computing the cluster hash slot of a key that is read in pieces.
+///
+/// Hash tags are not parsed, so callers with keys containing `{` should only feed in the hash tag.
+pub struct Crc16(State<XMODEM>);
+
+impl Crc16 {
+
+  pub fn new() -> Crc16 {
+    Crc16(State::<XMODEM>::new())
+  }
+
+  pub fn update(&mut self, bytes: &[u8]) {
+    self.0.update(bytes);
+  }
+
+  /// Read the cluster hash slot of the bytes fed into the hasher.
+  pub fn finish(self) -> u16 {
+    self.0.get() % REDIS_CLUSTER_SLOTS
+  }
+
+}
+
+impl Default for Crc16 {
+  fn default() -> Crc16 {
+    Crc16::new()
+  }
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// A 64 bit FNV-1a hasher, used where a digest must be stable across processes.
+pub struct Fnv64(u64);
+
+impl Fnv64 {
+
+  pub fn new() -> Fnv64 {
+    Fnv64(FNV_OFFSET_BASIS)
+  }
+
+  pub fn write(&mut self, bytes: &[u8]) {
+    for b in bytes.iter() {
+      self.0 ^= u64::from(*b);
+      self.0 = self.0.wrapping_mul(FNV_PRIME);
+    }
+  }
+
+  pub fn finish(&self) -> u64 {
+    self.0
+  }
+
+}
+
+// used to hash formatted numbers without allocating
+impl fmt::Write for Fnv64 {
+  fn write_str(&mut self, s: &str) -> fmt::Result {
+    self.write(s.as_bytes());
+    Ok(())
+  }
+}
+
+fn hash_header(hasher: &mut Fnv64, kind: FrameKind, len: usize) {
+  hasher.write(&[kind.to_byte()]);
+  let _ = write!(hasher, "{}", len);
+  hasher.write(CRLF.as_bytes());
+}
+
+/// Feed the bytes that `encode` writes for `frame` into `hasher`, without encoding the frame into a buffer first.
+pub fn hash_frame(hasher: &mut Fnv64, frame: &Frame) {
+  match *frame {
+    Frame::BulkString(ref b) => {
+      hash_header(hasher, FrameKind::BulkString, b.len());
+      hasher.write(b);
+      hasher.write(CRLF.as_bytes());
+    },
+    Frame::Array(ref frames)
+      | Frame::Set(ref frames)
+      | Frame::Push(ref frames) => {
+      hash_header(hasher, frame.kind(), frames.len());
+      for inner in frames.iter() {
+        hash_frame(hasher, inner);
+      }
+    },
+    Frame::Map(ref pairs)
+      | Frame::Attribute(ref pairs) => {
+      hash_header(hasher, frame.kind(), pairs.len());
+      for (key, value) in pairs.iter() {
+        hash_frame(hasher, key);
+        hash_frame(hasher, value);
+      }
+    },
+    Frame::Null      => hasher.write(NULL.as_bytes()),
+    Frame::NullResp3 => hasher.write(RESP3_NULL.as_bytes()),
+    Frame::SimpleString(ref s)
+      | Frame::Error(ref s)
+      | Frame::Moved(ref s)
+      | Frame::Ask(ref s)
+      | Frame::BigNumber(ref s) => {
+      hasher.write(&[frame.kind().to_byte()]);
+      hasher.write(s.as_bytes());
+      hasher.write(CRLF.as_bytes());
+    },
+    Frame::Integer(ref i) => {
+      let _ = write!(hasher, ":{}{}", i, CRLF);
+    },
+    Frame::Double(ref d) => {
+      let _ = write!(hasher, ",{}{}", double_to_string(*d), CRLF);
+    },
+    Frame::Boolean(ref b) => hasher.write(if *b { b"#t\r\n" }else{ b"#f\r\n" }),
+    Frame::VerbatimString { ref format, ref data } => {
+      hash_header(hasher, FrameKind::VerbatimString, VERBATIM_FORMAT_LEN + data.len());
+      hasher.write(&format.to_bytes());
+      hasher.write(b":");
+      hasher.write(data);
+      hasher.write(CRLF.as_bytes());
+    }
+  }
 }
 
 /// Map a Redis key to its cluster key slot.
@@ -171,7 +566,7 @@ pub fn redis_keyslot(key: &str) -> u16 {
   }
 
   if i.is_none() || (i.is_some() && i.unwrap() == key.len() - 1) {
-    return crc16_xmodem(key);
+    return crc16_xmodem(key.as_bytes());
   }
 
   let i = i.unwrap();
@@ -183,20 +578,32 @@ pub fn redis_keyslot(key: &str) -> u16 {
   }
 
   if j.is_none() {
-    return crc16_xmodem(key);
+    return crc16_xmodem(key.as_bytes());
   }
 
   let j = j.unwrap();
   let out = if i+j == key.len() || j == 0 {
-    crc16_xmodem(key)
+    crc16_xmodem(key.as_bytes())
   }else{
-    crc16_xmodem(&key[i+1..i+j+1])
+    crc16_xmodem(&key.as_bytes()[i+1..i+j+1])
   };
 
   trace!("mapped {} to redis slot {}", key, out);
   out
 }
 
+/// Map a Redis key that may not be valid UTF-8 to its cluster key slot.
+pub fn redis_keyslot_bytes(key: &[u8]) -> u16 {
+  let hash_tag = key.iter().position(|b| *b == b'{').and_then(|i| {
+    key[i + 1..].iter().position(|b| *b == b'}').map(|j| &key[i + 1..i + 1 + j])
+  });
+
+  match hash_tag {
+    Some(tag) if !tag.is_empty() => crc16_xmodem(tag),
+    _                            => crc16_xmodem(key)
+  }
+}
+
 pub fn read_cluster_error(payload: &str) -> Option<Frame> {
   if payload.starts_with("MOVED") {
     let parts: Vec<&str> = payload.split(" ").collect();
@@ -209,6 +616,70 @@ pub fn read_cluster_error(payload: &str) -> Option<Frame> {
   }
 }
 
+/// Map a custom nom error code from the decoder to a description.
+pub fn decode_error_description(code: u32) -> &'static str {
+  match code {
+    INVALID_LENGTH_ERROR        => "Invalid length prefix.",
+    LINE_TOO_LONG_ERROR         => "Line too long.",
+    INVALID_INTEGER_ERROR       => "Invalid integer.",
+    MAX_DEPTH_ERROR             => "Max depth exceeded.",
+    NOT_SCALAR_ERROR            => "Expected scalar frame.",
+    INVALID_VERBATIM_ERROR      => "Invalid verbatim string format.",
+    DUPLICATE_SET_ELEMENT_ERROR => "Duplicate set element.",
+    LENGTH_OUT_OF_RANGE_ERROR   => "Length prefix out of range.",
+    INVALID_STREAM_END_ERROR    => "Invalid streamed aggregate terminator.",
+    _                           => "Invalid frame."
+  }
+}
+
+/// Describe a length prefix that is too large, where `input` starts with the digits of the length prefix.
+pub fn length_out_of_range_description(input: &[u8]) -> String {
+  let digits: String = input.iter()
+    .take_while(|b| b.is_ascii_digit())
+    .map(|b| *b as char)
+    .collect();
+
+  format!("Length prefix out of range: {}.", digits)
+}
+
+pub fn truncate_bytes(b: &[u8], max: usize) -> Vec<u8> {
+  if b.len() <= max {
+    return b.to_vec();
+  }
+
+  let mut out = b[0..max].to_vec();
+  out.extend_from_slice(format!("...(+{} bytes)", b.len() - max).as_bytes());
+  out
+}
+
+pub fn truncate_string(s: &str, max: usize) -> String {
+  if s.len() <= max {
+    return s.to_owned();
+  }
+
+  let mut end = max;
+  while !s.is_char_boundary(end) {
+    end -= 1;
+  }
+
+  format!("{}...(+{} bytes)", &s[0..end], s.len() - end)
+}
+
+pub fn truncate_frames(frames: &[Frame], max: usize) -> Vec<Frame> {
+  frames.iter().map(|f| f.truncate_for_logging(max)).collect()
+}
+
+pub fn truncate_pairs(pairs: &[(Frame, Frame)], max: usize) -> Vec<(Frame, Frame)> {
+  pairs.iter().map(|(k, v)| (k.truncate_for_logging(max), v.truncate_for_logging(max))).collect()
+}
+
+pub fn view_to_string<'a>(s: &[u8]) -> Result<String, RedisProtocolError<'a>> {
+  match str::from_utf8(s) {
+    Ok(s) => Ok(s.to_owned()),
+    Err(_) => Err(RedisProtocolError::new(RedisProtocolErrorKind::DecodeError, "Invalid UTF-8 string."))
+  }
+}
+
 pub fn opt_frame_to_string_panic(f: Option<Frame>, msg: &str) -> String {
   f.expect(msg).to_string().expect(msg)
 }
@@ -225,6 +696,30 @@ pub fn is_pattern_pubsub(frames: &Vec<Frame>) -> bool {
     && frames[0].as_str().map(|s| s == PATTERN_PUBSUB_PREFIX).unwrap_or(false)
 }
 
+pub fn is_subscription_confirmation(frames: &[Frame]) -> bool {
+  frames.len() == 3
+    && frames[0].kind() == FrameKind::BulkString
+    && frames[0].as_str().map(|s| SUBSCRIPTION_KINDS.contains(&s)).unwrap_or(false)
+    && frames[2].kind() == FrameKind::Integer
+}
+
+/// Parse a `__keyspace@<db>__:<key>` or `__keyevent@<db>__:<event>` channel, returning whether it's a keyspace channel,
+/// the database index, and the remainder of the channel.
+pub fn read_keyspace_channel(channel: &str) -> Option<(bool, u32, &str)> {
+  let (is_keyspace, rest) = if let Some(rest) = channel.strip_prefix(KEYSPACE_PREFIX) {
+    (true, rest)
+  }else if let Some(rest) = channel.strip_prefix(KEYEVENT_PREFIX) {
+    (false, rest)
+  }else{
+    return None;
+  };
+
+  let end = rest.find(KEYSPACE_DB_SUFFIX)?;
+  let db = rest[0..end].parse::<u32>().ok()?;
+
+  Some((is_keyspace, db, &rest[end + KEYSPACE_DB_SUFFIX.len()..]))
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -260,6 +755,56 @@ mod tests {
     assert_eq!(integer_encode_len(&i2), 9);
   }
 
+  #[test]
+  fn should_count_digits_at_power_of_ten_boundaries() {
+    assert_eq!(digits_in_number(0), 1);
+    assert_eq!(integer_encode_len(&9_999_999_999_999_999), 19);
+    assert_eq!(integer_encode_len(&-9_999_999_999_999_999), 20);
+    assert_eq!(integer_encode_len(&999_999_999_999_999_999), 21);
+    assert_eq!(integer_encode_len(&i64::MAX), 22);
+    assert_eq!(integer_encode_len(&i64::MIN), 23);
+
+    let mut value: u64 = 9;
+    for digits in 1..20 {
+      assert_eq!(digits_in_u64(value), digits);
+      assert_eq!(digits_in_u64(value + 1), digits + 1);
+
+      if digits < 19 {
+        value = value * 10 + 9;
+      }
+    }
+    assert_eq!(digits_in_u64(u64::MAX), 20);
+  }
+
+  #[test]
+  fn should_get_encode_len_double() {
+    assert_eq!(double_encode_len(1.5), 6);
+    assert_eq!(double_encode_len(-3.0), 5);
+    assert_eq!(double_encode_len(f64::NAN), 6);
+    assert_eq!(double_encode_len(f64::NEG_INFINITY), 7);
+    assert_eq!(double_encode_len(1e100), 8);
+    assert_eq!(double_encode_len(f64::MIN_POSITIVE), 26);
+  }
+
+  #[test]
+  fn should_get_encode_len_bignumber() {
+    let frames = [
+      Frame::BigNumber("1234567890123456789012345678901234567890".into()),
+      Frame::BigNumber("-12345678901234567890".into())
+    ];
+
+    for frame in frames.iter() {
+      let s = match *frame {
+        Frame::BigNumber(ref s) => s,
+        _ => unreachable!()
+      };
+      let encoded = ::encode::encode_to_bytes(frame).unwrap();
+
+      assert_eq!(bignumber_encode_len(s), encoded.len());
+      assert_eq!(encode_len(frame), encoded.len());
+    }
+  }
+
   #[test]
   fn should_crc16_123456789() {
     let key = "123456789";
@@ -270,6 +815,16 @@ mod tests {
     assert_eq!(actual, expected);
   }
 
+  #[test]
+  fn should_crc16_incrementally() {
+    let mut crc = Crc16::new();
+    crc.update(b"12345");
+    crc.update(b"6789");
+
+    assert_eq!(crc.finish(), redis_keyslot("123456789"));
+    assert_eq!(Crc16::new().finish(), redis_keyslot(""));
+  }
+
   #[test]
   fn should_crc16_with_brackets() {
     let key = "foo{123456789}bar";
@@ -321,4 +876,96 @@ mod tests {
     assert_eq!(actual, expected);
   }
 
+  #[test]
+  fn should_crc16_bytes_same_as_str() {
+    let keys = ["123456789", "foo{123456789}bar", "{123456789}", "foo{123456789", "foo}123456789", "foo{}bar", "8xjx7vWrfPq54mKfFD3Y1CcjjofpnAcQ"];
+
+    for key in keys.iter() {
+      assert_eq!(redis_keyslot_bytes(key.as_bytes()), redis_keyslot(key));
+    }
+  }
+
+  #[test]
+  fn should_crc16_non_utf8_bytes_with_brackets() {
+    let key = [0xff, b'{', b'1', b'2', b'3', b'4', b'5', b'6', b'7', b'8', b'9', b'}', 0xfe];
+    assert_eq!(redis_keyslot_bytes(&key), 12739);
+  }
+
+  #[cfg(feature = "redirection-cache")]
+  #[test]
+  fn should_hit_redirection_cache() {
+    let cache = RedirectionCache::new(2);
+    let first = cache.parse("MOVED 3999 127.0.0.1:6381").unwrap();
+    let second = cache.parse("MOVED 3999 127.0.0.1:6381").unwrap();
+
+    assert_eq!(first, second);
+    assert_eq!(first, string_to_redirection("MOVED 3999 127.0.0.1:6381").unwrap());
+    assert_eq!(cache.misses(), 1);
+    assert_eq!(cache.hits(), 1);
+    assert_eq!(cache.len(), 1);
+  }
+
+  #[cfg(feature = "redirection-cache")]
+  #[test]
+  fn should_evict_least_recently_used_redirection() {
+    let cache = RedirectionCache::new(2);
+    cache.parse("MOVED 1 127.0.0.1:6381").unwrap();
+    cache.parse("ASK 2 127.0.0.1:6382").unwrap();
+    cache.parse("MOVED 1 127.0.0.1:6381").unwrap();
+    cache.parse("MOVED 3 127.0.0.1:6383").unwrap();
+    assert_eq!(cache.len(), 2);
+
+    cache.parse("MOVED 1 127.0.0.1:6381").unwrap();
+    assert_eq!(cache.hits(), 2);
+    cache.parse("ASK 2 127.0.0.1:6382").unwrap();
+    assert_eq!(cache.hits(), 2);
+    assert_eq!(cache.misses(), 4);
+  }
+
+  #[cfg(feature = "redirection-cache")]
+  #[test]
+  fn should_parse_frame_redirection_with_shared_cache() {
+    let frame = Frame::Moved("MOVED 4001 10.0.0.1:7001".into());
+    let expected = string_to_redirection("MOVED 4001 10.0.0.1:7001").unwrap();
+
+    assert_eq!(frame.to_redirection().unwrap(), expected);
+    let hits = RedirectionCache::shared().hits();
+    assert_eq!(frame.to_redirection().unwrap(), expected);
+    assert!(RedirectionCache::shared().hits() > hits);
+  }
+
+  #[cfg(feature = "redirection-cache")]
+  #[test]
+  fn should_update_recency_on_cache_hit() {
+    let cache = RedirectionCache::new(3);
+    for slot in 0..3 {
+      cache.parse(&format!("MOVED {} 127.0.0.1:6381", slot)).unwrap();
+    }
+
+    cache.parse("MOVED 0 127.0.0.1:6381").unwrap();
+    cache.parse("MOVED 3 127.0.0.1:6381").unwrap();
+    assert_eq!(cache.len(), 3);
+
+    cache.parse("MOVED 0 127.0.0.1:6381").unwrap();
+    cache.parse("MOVED 2 127.0.0.1:6381").unwrap();
+    assert_eq!(cache.hits(), 3);
+    cache.parse("MOVED 1 127.0.0.1:6381").unwrap();
+    assert_eq!(cache.hits(), 3);
+    assert_eq!(cache.misses(), 5);
+  }
+
+  #[cfg(feature = "redirection-cache")]
+  #[test]
+  fn should_not_cache_invalid_redirection() {
+    let cache = RedirectionCache::new(2);
+
+    assert!(cache.parse("MOVED foo 127.0.0.1:6381").is_err());
+    assert!(cache.parse("MOVED foo 127.0.0.1:6381").is_err());
+    assert!(cache.is_empty());
+    assert_eq!(cache.misses(), 2);
+
+    cache.clear();
+    assert_eq!(cache.misses(), 0);
+  }
+
 }